@@ -0,0 +1,38 @@
+//! Compares copying a boxed slice into another arena to cloning it element by element.
+//!
+//! Run with ``cargo bench``.
+
+use std::time::{Duration, Instant};
+
+use arena::{Arena, ArenaAlloc, ArenaBox};
+
+const N: usize = 1_000_000;
+const RUNS: u32 = 20;
+
+fn bench(name: &str, source: &ArenaBox<[u64]>, f: impl Fn(&ArenaBox<[u64]>, &mut ArenaAlloc)) {
+	let mut arena = Arena::new(N * std::mem::size_of::<u64>() + 64);
+	let mut total = Duration::default();
+
+	for _ in 0..RUNS {
+		let mut alloc = arena.begin_alloc();
+		let start = Instant::now();
+		f(source, &mut alloc);
+		total += start.elapsed();
+	}
+
+	println!("{:<30} {:?} per run", name, total / RUNS);
+}
+
+fn main() {
+	let mut source_arena = Arena::new(N * std::mem::size_of::<u64>() + 64);
+	let mut source_alloc = source_arena.begin_alloc();
+	let source = source_alloc.insert_all(0..N as u64);
+
+	bench("copy_slice_in", &source, |source, alloc| {
+		std::hint::black_box(source.copy_slice_in(alloc));
+	});
+
+	bench("clone_slice_in", &source, |source, alloc| {
+		std::hint::black_box(source.clone_slice_in(alloc));
+	});
+}
@@ -0,0 +1,43 @@
+//! Compares inserting iterators with an exact size hint to ones without any size hint, and to
+//! inserting the elements one at a time, which is what ``insert_all`` used to do past the hint.
+//!
+//! Run with ``cargo bench``.
+
+use std::time::{Duration, Instant};
+
+use arena::Arena;
+
+const N: u64 = 1_000_000;
+const RUNS: u32 = 20;
+
+fn bench(name: &str, mut f: impl FnMut(&mut Arena)) {
+	let mut arena = Arena::new(N as usize * std::mem::size_of::<u64>() + 64);
+	let mut total = Duration::default();
+
+	for _ in 0..RUNS {
+		let start = Instant::now();
+		f(&mut arena);
+		total += start.elapsed();
+	}
+
+	println!("{:<30} {:?} per run", name, total / RUNS);
+}
+
+fn main() {
+	bench("insert_all, exact size hint", |arena| {
+		let mut alloc = arena.begin_alloc();
+		std::hint::black_box(alloc.insert_all(0..N));
+	});
+
+	bench("insert_all, no size hint", |arena| {
+		let mut alloc = arena.begin_alloc();
+		std::hint::black_box(alloc.insert_all((0..N).filter(|_| true)));
+	});
+
+	bench("insert one at a time", |arena| {
+		let mut alloc = arena.begin_alloc();
+		for i in 0..N {
+			std::hint::black_box(alloc.insert(i).leak());
+		}
+	});
+}
@@ -1,17 +1,63 @@
-use std::ops::{Deref, DerefMut};
-use std::marker::PhantomData;
-use std::mem;
-use std::iter::FusedIterator;
-use std::hash::{Hash, Hasher};
+use core::any::Any;
+use core::ops::{Bound, Deref, DerefMut, Range, RangeBounds};
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::iter::FusedIterator;
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(feature = "std")]
 use std::io::{Read, Write, BufRead, IoSliceMut, IoSlice, SeekFrom, Seek};
-use std::fmt;
-use std::future::Future;
-use std::task::{Poll, Context};
-use std::pin::Pin;
-use std::borrow::{Borrow, BorrowMut};
-
-/// Similar to [Box] except it does not drop the memory location.
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::future::Future;
+use core::task::{Poll, Context};
+use core::pin::Pin;
+use core::borrow::{Borrow, BorrowMut};
+#[cfg(feature = "nightly")]
+use core::marker::Unsize;
+#[cfg(feature = "nightly")]
+use core::ops::CoerceUnsized;
+
+use crate::{ArenaAlloc, ArenaBoxIntoIter};
+
+/// Similar to [Box](alloc::boxed::Box) except it does not drop the memory location.
+///
+/// Like a [Box](alloc::boxed::Box), this is [Send] and [Sync] only if T is, so this doesn't
+/// compile:
+/// ```compile_fail
+/// use std::rc::Rc;
+///
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let value = alloc.insert(Rc::new(5));
+/// std::thread::scope(|s| {
+///     s.spawn(move || drop(value));
+/// });
+/// ```
+///
+/// With the ``nightly`` feature, boxes coerce to unsized types the same way a
+/// [Box](alloc::boxed::Box) does, so the result of [ArenaAlloc::insert] can be bound to a boxed
+/// trait object or slice directly. Dropping the coerced box still drops the original value.
+/// ```
+/// # #[cfg(feature = "nightly")] {
+/// use arena::ArenaBox;
+/// use std::fmt::Display;
+///
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let number: ArenaBox<dyn Display> = alloc.insert(5i32);
+/// let bytes: ArenaBox<[u8]> = alloc.insert([1u8, 2, 3]);
+/// assert_eq!(number.to_string(), "5");
+/// assert_eq!(&*bytes, &[1, 2, 3]);
+/// # }
+/// ```
+///
+/// A box has the same layout as a ``*mut T``, so for a sized T it can be passed across an
+/// ``extern "C"`` boundary as a plain pointer.
+#[repr(transparent)]
 pub struct ArenaBox<'a, T: ?Sized> {
 	// INVARIANT: buffer has to live for at least as long as 'a, it cannot be accessed by anything
 	// else for 'a, and it has to be a valid T.
@@ -19,16 +65,449 @@ pub struct ArenaBox<'a, T: ?Sized> {
 	_phantom: PhantomData<&'a mut T>,
 }
 
+// Lets an ArenaBox<[T; N]> coerce to an ArenaBox<[T]>, and an ArenaBox<T> to an
+// ArenaBox<dyn Trait>, just like a Box.
+#[cfg(feature = "nightly")]
+impl<'a, T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<ArenaBox<'a, U>> for ArenaBox<'a, T> {}
+
+// The box is only a pointer, the PhantomData doesn't take up any space.
+const _: () = assert!(
+	mem::size_of::<ArenaBox<u32>>() == mem::size_of::<*mut u32>()
+		&& mem::align_of::<ArenaBox<u32>>() == mem::align_of::<*mut u32>()
+);
+
+// SAFETY: The box uniquely owns its T, so it's like a &'a mut T, which is Send if T is Send,
+// and Sync if T is Sync.
+unsafe impl<T: ?Sized + Send> Send for ArenaBox<'_, T> {}
+unsafe impl<T: ?Sized + Sync> Sync for ArenaBox<'_, T> {}
+
 impl<'a, E> ArenaBox<'a, [E]> {
-	/// Creates an [ArenaBox] containing an empty slice. This does not perform an allocation.
+	/// Creates an [ArenaBox] containing an empty slice. This does not perform an allocation, so it
+	/// doesn't need an allocator.
+	///
+	/// The slice points to a dangling, but aligned, pointer. Since it has no elements, dropping it
+	/// doesn't do anything.
 	pub fn empty_slice() -> Self {
 		Self {
-			buffer: unsafe {
-				std::slice::from_raw_parts_mut(std::ptr::NonNull::dangling().as_ptr(), 0)
-			},
+			buffer: core::ptr::slice_from_raw_parts_mut(core::ptr::NonNull::dangling().as_ptr(), 0),
 			_phantom: PhantomData,
 		}
 	}
+
+	/// Creates a boxed slice from a pointer to its first element and its length. Like
+	/// [ArenaBox::from_raw], this box will not free the memory when dropped.
+	///
+	/// # Safety
+	/// * The pointer has to be valid for ``len`` elements for 'a
+	/// * They cannot be accessed by anything else during that time
+	/// * All ``len`` elements have to be valid Es.
+	pub unsafe fn from_raw_parts(ptr: *mut E, len: usize) -> Self {
+		Self::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len))
+	}
+
+	/// Converts the box into a pointer to its first element and its length, without dropping
+	/// the elements. [ArenaBox::from_raw_parts] turns them back into a box.
+	pub fn into_raw_parts(self) -> (*mut E, usize) {
+		let len = self.len();
+		(self.into_raw() as *mut E, len)
+	}
+
+	/// Returns an iterator that moves the elements out of the slice. The elements that aren't
+	/// yielded are dropped along with the iterator.
+	///
+	/// This can't be an implementation of [IntoIterator], since it would overlap with the
+	/// [Iterator] implementation for boxed iterators.
+	#[allow(clippy::should_implement_trait)]
+	pub fn into_iter(self) -> ArenaBoxIntoIter<'a, E> {
+		let slice = self.into_raw();
+		// SAFETY: The box owned the elements, and it was consumed.
+		unsafe { ArenaBoxIntoIter::new(slice as *mut E, slice.len()) }
+	}
+
+	/// Borrows the elements in a range of the slice.
+	///
+	/// # Panics
+	/// * If the range is out of bounds, or if it starts after it ends.
+	pub fn slice(&self, range: impl RangeBounds<usize>) -> &[E] {
+		let range = resolve_range(range, self.len());
+		&self.as_ref()[range]
+	}
+
+	/// Mutably borrows the elements in a range of the slice.
+	///
+	/// # Panics
+	/// * If the range is out of bounds, or if it starts after it ends.
+	pub fn slice_mut(&mut self, range: impl RangeBounds<usize>) -> &mut [E] {
+		let range = resolve_range(range, self.len());
+		&mut self.as_mut()[range]
+	}
+
+	/// Borrows the first element and the rest of the slice, or returns None if it's empty.
+	pub fn split_first(&self) -> Option<(&E, &[E])> {
+		self.as_ref().split_first()
+	}
+
+	/// Mutably borrows the first element and the rest of the slice, or returns None if it's
+	/// empty.
+	pub fn split_first_mut(&mut self) -> Option<(&mut E, &mut [E])> {
+		self.as_mut().split_first_mut()
+	}
+
+	/// Borrows the last element and the rest of the slice, or returns None if it's empty.
+	pub fn split_last(&self) -> Option<(&E, &[E])> {
+		self.as_ref().split_last()
+	}
+
+	/// Mutably borrows the last element and the rest of the slice, or returns None if it's
+	/// empty.
+	pub fn split_last_mut(&mut self) -> Option<(&mut E, &mut [E])> {
+		self.as_mut().split_last_mut()
+	}
+
+	/// Overwrites every element with a clone of ``value``, dropping the old elements. Like
+	/// [slice::fill], the last element gets ``value`` itself.
+	pub fn fill(&mut self, value: E)
+		where E: Clone
+	{
+		self.as_mut().fill(value)
+	}
+
+	/// Overwrites every element with the values returned by calling ``f`` repeatedly, dropping
+	/// the old elements.
+	pub fn fill_with(&mut self, f: impl FnMut() -> E) {
+		self.as_mut().fill_with(f)
+	}
+
+	/// Splits the box into two boxes at an index, without copying. The first box contains the
+	/// elements in ``[0, mid)``, and the second the elements in ``[mid, len)``. Each box drops
+	/// the elements it contains.
+	///
+	/// This consumes the box, to borrow the two halves use ``(**self).split_at(mid)`` instead.
+	///
+	/// # Panics
+	/// * If ``mid > len``.
+	pub fn split_at(self, mid: usize) -> (Self, Self) {
+		let len = self.len();
+		assert!(mid <= len, "mid is out of bounds");
+
+		let ptr = self.into_raw() as *mut E;
+		// SAFETY: The two halves don't overlap, and together they cover the whole slice, so each
+		// element is owned by exactly one of them.
+		unsafe {
+			(
+				ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, mid)),
+				ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(ptr.add(mid), len - mid)),
+			)
+		}
+	}
+
+	/// Splits the box into boxes of ``chunk_size`` elements each, without copying. The last box
+	/// may be shorter. Each box drops the elements it contains, so they can be moved to different
+	/// threads, for example.
+	///
+	/// # Panics
+	/// * If ``chunk_size`` is 0.
+	pub fn into_chunks(self, chunk_size: usize) -> Vec<Self> {
+		assert!(chunk_size != 0, "chunk size must be non-zero");
+
+		let len = self.len();
+		let ptr = self.into_raw() as *mut E;
+		(0..len)
+			.step_by(chunk_size)
+			.map(|start| {
+				let chunk_len = chunk_size.min(len - start);
+				// SAFETY: The chunks don't overlap, and together they cover the whole slice, so
+				// each element is owned by exactly one of them.
+				unsafe {
+					let chunk = core::ptr::slice_from_raw_parts_mut(ptr.add(start), chunk_len);
+					ArenaBox::from_raw(chunk)
+				}
+			})
+			.collect()
+	}
+
+	/// Clones the elements into a [Vec], which can outlive the arena.
+	pub fn to_vec(&self) -> Vec<E>
+		where E: Clone
+	{
+		self.as_ref().to_vec()
+	}
+
+	/// Moves the elements into a [Vec], which can outlive the arena.
+	pub fn into_vec(self) -> Vec<E> {
+		let len = self.len();
+		let mut vec = Vec::with_capacity(len);
+		let slice = self.into_raw();
+		// SAFETY: The box was consumed without dropping its elements, so they are moved into the
+		// vector, which has space for all of them.
+		unsafe {
+			core::ptr::copy_nonoverlapping(slice as *const E, vec.as_mut_ptr(), len);
+			vec.set_len(len);
+		}
+		vec
+	}
+
+	/// Tries to copy the slice into another allocator. If there isn't enough space, it will return
+	/// None.
+	///
+	/// This copies the whole slice at once, instead of cloning the elements one by one like
+	/// [ArenaBox::try_clone_slice_in] does.
+	pub fn try_copy_slice_in<'b>(&self, alloc: &mut ArenaAlloc<'b>) -> Option<ArenaBox<'b, [E]>>
+		where E: Copy
+	{
+		alloc.try_insert_slice(self)
+	}
+
+	/// Copies the slice into another allocator.
+	///
+	/// This copies the whole slice at once, instead of cloning the elements one by one like
+	/// [ArenaBox::clone_slice_in] does.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the allocator.
+	pub fn copy_slice_in<'b>(&self, alloc: &mut ArenaAlloc<'b>) -> ArenaBox<'b, [E]>
+		where E: Copy
+	{
+		alloc.insert_slice(self)
+	}
+
+	/// Tries to clone the slice into another allocator. If there isn't enough space, it will
+	/// return None.
+	pub fn try_clone_slice_in<'b>(&self, alloc: &mut ArenaAlloc<'b>) -> Option<ArenaBox<'b, [E]>>
+		where E: Clone
+	{
+		alloc.try_insert_slice_clone(self)
+	}
+
+	/// Clones the slice into another allocator.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the allocator.
+	pub fn clone_slice_in<'b>(&self, alloc: &mut ArenaAlloc<'b>) -> ArenaBox<'b, [E]>
+		where E: Clone
+	{
+		alloc.insert_slice_clone(self)
+	}
+}
+
+/// Turns range bounds into a range of indices into a slice of ``len`` elements, with panic
+/// messages that say what was wrong with the range.
+fn resolve_range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+	let start = match range.start_bound() {
+		Bound::Included(&start) => start,
+		Bound::Excluded(&start) => start.checked_add(1).expect("range start overflowed"),
+		Bound::Unbounded => 0,
+	};
+	let end = match range.end_bound() {
+		Bound::Included(&end) => end.checked_add(1).expect("range end overflowed"),
+		Bound::Excluded(&end) => end,
+		Bound::Unbounded => len,
+	};
+
+	assert!(end <= len, "range end {} is out of bounds for a slice of length {}", end, len);
+	assert!(start <= end, "range starts at {} but ends at {}", start, end);
+	start..end
+}
+
+#[cfg(feature = "std")]
+impl<'a> ArenaBox<'a, [u8]> {
+	/// Returns a reader over the bytes, which implements [Read].
+	pub fn reader(self) -> crate::ArenaReader<'a> {
+		crate::ArenaReader::new(self)
+	}
+}
+
+#[cfg(feature = "bytemuck")]
+impl<'a, E> ArenaBox<'a, [E]> where E: bytemuck::Pod {
+	/// Reinterprets the elements as another plain old data type, without copying them. See
+	/// [bytemuck::try_cast_slice].
+	///
+	/// # Errors
+	/// If the start of the slice isn't aligned for U, or its size in bytes isn't a multiple of the
+	/// size of U, the box is given back unchanged, along with the reason.
+	pub fn cast_slice<U>(mut self) -> Result<ArenaBox<'a, [U]>, (Self, bytemuck::PodCastError)>
+		where U: bytemuck::Pod
+	{
+		match bytemuck::try_cast_slice_mut::<E, U>(&mut self) {
+			Ok(slice) => {
+				let slice = slice as *mut [U];
+				// The elements are plain old data, so there's nothing to drop.
+				let _ = self.into_raw();
+				// SAFETY: The new slice covers the same memory as the old one, which is owned by
+				// the box, and bytemuck checked that it's valid for U.
+				unsafe { Ok(ArenaBox::from_raw(slice)) }
+			}
+			Err(error) => Err((self, error)),
+		}
+	}
+}
+
+impl<'a, T, const N: usize> ArenaBox<'a, [T; N]> {
+	/// Converts a boxed array into a boxed slice, without copying it.
+	pub fn into_slice(self) -> ArenaBox<'a, [T]> {
+		// SAFETY: An array has the same layout as a slice with the same number of elements.
+		unsafe { ArenaBox::from_raw(self.into_raw() as *mut [T]) }
+	}
+}
+
+impl<'a> ArenaBox<'a, dyn Any> {
+	/// Attempts to downcast the box to a concrete type. If it isn't of that type, the box is
+	/// returned unchanged.
+	pub fn downcast<T: Any>(self) -> Result<ArenaBox<'a, T>, Self> {
+		if self.as_ref().is::<T>() {
+			// SAFETY: We just checked that the contained element is a T.
+			unsafe { Ok(ArenaBox::from_raw(self.into_raw() as *mut T)) }
+		} else {
+			Err(self)
+		}
+	}
+
+	/// Returns a reference to the contained element if it is a T, or None if it isn't.
+	pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+		self.as_ref().downcast_ref()
+	}
+
+	/// Returns a mutable reference to the contained element if it is a T, or None if it isn't.
+	pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+		self.as_mut().downcast_mut()
+	}
+}
+
+impl<'a, T> ArenaBox<'a, MaybeUninit<T>> {
+	/// Converts the box into a box of an initialized T.
+	///
+	/// # Safety
+	/// * The contained value has to be fully initialized.
+	pub unsafe fn assume_init(self) -> ArenaBox<'a, T> {
+		ArenaBox::from_raw(self.into_raw() as *mut T)
+	}
+
+	/// Writes a value into the box, and converts it into a box of the initialized T.
+	pub fn write(mut self, value: T) -> ArenaBox<'a, T> {
+		self.as_mut().write(value);
+		// SAFETY: We just initialized the value.
+		unsafe { self.assume_init() }
+	}
+}
+
+impl<'a, T> ArenaBox<'a, [MaybeUninit<T>]> {
+	/// Converts the box into a box of an initialized slice of T.
+	///
+	/// # Safety
+	/// * All the elements of the slice have to be fully initialized.
+	pub unsafe fn assume_init(self) -> ArenaBox<'a, [T]> {
+		ArenaBox::from_raw(self.into_raw() as *mut [T])
+	}
+}
+
+impl<'a, T> ArenaBox<'a, T> {
+	/// Moves the contained element out of the box.
+	pub fn into_inner(self) -> T {
+		let ptr = self.into_raw();
+		// SAFETY: The pointer points to a valid T, and since we consumed the box, nothing else
+		// is going to read it or drop it.
+		unsafe { ptr.read() }
+	}
+
+	/// Moves the contained element out of the box, the same as [ArenaBox::into_inner].
+	///
+	/// Unlike an ``Rc``, an [ArenaBox] is always the only owner of its element, so moving it out
+	/// can't fail and there is no need for a ``try_unwrap``.
+	pub fn into_value(self) -> T {
+		self.into_inner()
+	}
+
+	/// Tries to clone the contained element into another allocator. If there isn't enough space,
+	/// it will return None.
+	///
+	/// [ArenaBox] can't implement [Clone], because it doesn't know which allocator to use.
+	pub fn try_clone_in<'b>(&self, alloc: &mut ArenaAlloc<'b>) -> Option<ArenaBox<'b, T>>
+		where T: Clone
+	{
+		alloc.try_insert_with(|| self.as_ref().clone())
+	}
+
+	/// Clones the contained element into another allocator.
+	///
+	/// [ArenaBox] can't implement [Clone], because it doesn't know which allocator to use.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the allocator.
+	pub fn clone_in<'b>(&self, alloc: &mut ArenaAlloc<'b>) -> ArenaBox<'b, T>
+		where T: Clone
+	{
+		alloc.insert_with(|| self.as_ref().clone())
+	}
+
+	/// Replaces the contained element with the result of ``f``, reusing the same memory.
+	///
+	/// Since the memory is reused, the new element has to fit into the memory of the old one.
+	/// If it doesn't, allocate a new box with [ArenaAlloc::insert] instead.
+	///
+	/// # Panics
+	/// * If ``U`` is larger than ``T``, or has a larger alignment.
+	///
+	/// [ArenaAlloc::insert]: crate::ArenaAlloc::insert
+	pub fn map<U, F>(self, f: F) -> ArenaBox<'a, U>
+		where F: FnOnce(T) -> U
+	{
+		assert!(
+			mem::size_of::<U>() <= mem::size_of::<T>()
+				&& mem::align_of::<U>() <= mem::align_of::<T>(),
+			"the new element does not fit in the memory of the old one"
+		);
+
+		let ptr = self.into_raw();
+		// SAFETY: The pointer points to a valid T, and since we consumed the box, nothing else
+		// is going to read it or drop it. The memory is large enough and aligned for a U, as
+		// alignments are powers of two.
+		unsafe {
+			let value = f(ptr.read());
+			let ptr = ptr as *mut U;
+			ptr.write(value);
+			ArenaBox::from_raw(ptr)
+		}
+	}
+
+	/// Reinterprets the box as containing a U instead of a T, without moving or converting the
+	/// element.
+	///
+	/// # Safety
+	/// * ``U`` has to be the same size as ``T``. This is checked in debug builds.
+	/// * The element has to be aligned for ``U``. If ``U`` has a larger alignment than ``T``, it
+	///   has to have been allocated with a larger alignment, for example with
+	///   [ArenaAlloc::insert_aligned]. This is checked in debug builds.
+	/// * The bytes of the element have to be a valid ``U``, and the ``U`` has to be fine to drop
+	///   instead of the ``T``. For example, casting a ``[u8; 4]`` to a ``u32`` is fine, but
+	///   casting a ``u32`` to a ``char`` may not be, and casting anything to a reference never is
+	///   unless the bytes came from one.
+	pub unsafe fn cast<U>(self) -> ArenaBox<'a, U> {
+		debug_assert_eq!(
+			mem::size_of::<T>(),
+			mem::size_of::<U>(),
+			"the types have different sizes"
+		);
+		let ptr = self.into_raw() as *mut U;
+		debug_assert_eq!(
+			ptr as usize % mem::align_of::<U>(),
+			0,
+			"the element is not aligned for U"
+		);
+		ArenaBox::from_raw(ptr)
+	}
+
+	/// Swaps the elements the two boxes contain, without moving the boxes themselves.
+	///
+	/// To swap which allocations the boxes point to instead, use [ArenaBox::swap_ptrs].
+	pub fn swap(a: &mut Self, b: &mut Self) {
+		mem::swap(a.as_mut(), b.as_mut());
+	}
+
+	/// Replaces the contained value with a new one in the same allocation, and returns the old
+	/// one, like [mem::replace].
+	pub fn replace(&mut self, value: T) -> T {
+		mem::replace(self.as_mut(), value)
+	}
 }
 
 impl<'a, T> ArenaBox<'a, T> where T: ?Sized {
@@ -58,6 +537,7 @@ impl<'a, T> ArenaBox<'a, T> where T: ?Sized {
 	}
 
 	/// Returns a reference to the contained element.
+	#[allow(clippy::should_implement_trait)]
 	pub fn as_ref(&self) -> &T {
 		// SAFETY: (from invariants)
 		// self.buffer is only accessed by this struct, it is also nonnull and valid
@@ -65,6 +545,7 @@ impl<'a, T> ArenaBox<'a, T> where T: ?Sized {
 	}
 
 	/// Returns a mutable reference to the contained element.
+	#[allow(clippy::should_implement_trait)]
 	pub fn as_mut(&mut self) -> &mut T {
 		// SAFETY: (from invariants)
 		// self.buffer is only accessed by this struct, it is also nonnull and valid
@@ -91,9 +572,60 @@ impl<'a, T> ArenaBox<'a, T> where T: ?Sized {
 		self.buffer
 	}
 
+	/// Returns true if the two boxes point to the same allocation, without comparing the
+	/// contained elements like ``==`` does.
+	///
+	/// Only the addresses are compared, so zero sized elements may compare equal.
+	pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+		core::ptr::addr_eq(a.buffer, b.buffer)
+	}
+
+	/// Pins the box, so that the contained element can't be moved out of it. Since
+	/// ``Pin<ArenaBox<F>>`` is a [Future] whenever ``F`` is, this lets you poll futures that
+	/// aren't [Unpin], like the ones ``async`` blocks create.
+	///
+	/// # Safety
+	/// * The box has to be dropped before the memory is reused by the next batch of allocations,
+	///   it may not be forgotten or leaked. Pinning promises that the memory of the element isn't
+	///   reused until it has been dropped, and the arena can't keep that promise on its own.
+	pub unsafe fn into_pin(self) -> Pin<Self> {
+		Pin::new_unchecked(self)
+	}
+
+	/// Swaps the allocations the two boxes point to, without touching the elements.
+	///
+	/// This is the same as swapping the boxes themselves, which is fine since both boxes live
+	/// for ``'a``.
+	pub fn swap_ptrs(a: &mut Self, b: &mut Self) {
+		mem::swap(&mut a.buffer, &mut b.buffer);
+	}
+
+	/// Forgets the box without dropping the contained element, like [mem::forget].
+	///
+	/// This only skips the [Drop] of the element, the memory it's in is still reused by the next
+	/// batch of allocations like everything else in the [Arena](crate::Arena).
+	pub fn forget(self) {
+		mem::forget(self);
+	}
+
 	/// Leaks the box. This does not return a 'static reference because [ArenaBox] does not own
 	/// it's memory, hence this doesn't leak the memory which T resides in, but rather just doesn't
 	/// call drop on T.
+	///
+	/// This works for unsized types too, so leaking a boxed slice gives you a ``&'a mut [T]``,
+	/// and leaking a boxed str gives you a ``&'a mut str``:
+	/// ```
+	/// let mut arena = arena::Arena::new(64);
+	/// let mut alloc = arena.begin_alloc();
+	///
+	/// let slice: &mut [u32] = alloc.insert_slice(&[1, 2, 3]).leak();
+	/// slice[0] = 4;
+	/// assert_eq!(slice, &[4, 2, 3]);
+	///
+	/// let string: &mut str = alloc.insert_str("hello").leak();
+	/// string.make_ascii_uppercase();
+	/// assert_eq!(string, "HELLO");
+	/// ```
 	pub fn leak(self) -> &'a mut T {
 		let mut s = mem::ManuallyDrop::new(self);
 		// This is safe for the same reason that ``as_mut`` is safe.
@@ -113,6 +645,12 @@ impl<T> fmt::Display for ArenaBox<'_, T> where T: fmt::Display + ?Sized {
 	}
 }
 
+impl<T: ?Sized> fmt::Pointer for ArenaBox<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Pointer::fmt(&self.as_ptr(), f)
+	}
+}
+
 impl<T: ?Sized> Borrow<T> for ArenaBox<'_, T> {
 	fn borrow(&self) -> &T {
 		self.as_ref()
@@ -142,20 +680,34 @@ impl<T: ?Sized> DerefMut for ArenaBox<'_, T> {
 impl<T: ?Sized> Drop for ArenaBox<'_, T> {
 	fn drop(&mut self) {
 		unsafe {
-			std::ptr::drop_in_place(self.buffer);
+			core::ptr::drop_in_place(self.buffer);
 		}
 	}
 }
 
-impl<T> std::convert::AsMut<T> for ArenaBox<'_, T> {
+impl<T> core::convert::AsMut<T> for ArenaBox<'_, T> {
 	fn as_mut(&mut self) -> &mut T {
 		&mut *self
 	}
 }
 
-impl<T> std::convert::AsRef<T> for ArenaBox<'_, T> {
+impl<T> core::convert::AsRef<T> for ArenaBox<'_, T> {
 	fn as_ref(&self) -> &T {
-		&*self
+		self
+	}
+}
+
+impl core::convert::AsRef<str> for ArenaBox<'_, str> {
+	fn as_ref(&self) -> &str {
+		self
+	}
+}
+
+// There is no ``AsMut<[u8]>``, since writing arbitrary bytes could make the str invalid utf-8.
+// ``String`` doesn't have one for the same reason.
+impl core::convert::AsRef<[u8]> for ArenaBox<'_, str> {
+	fn as_ref(&self) -> &[u8] {
+		self.as_bytes()
 	}
 }
 
@@ -175,7 +727,7 @@ impl<T> Iterator for ArenaBox<'_, T> where T: Iterator + ?Sized {
 
 impl<T> DoubleEndedIterator for ArenaBox<'_, T> where T: DoubleEndedIterator + ?Sized {
 	fn next_back(&mut self) -> Option<Self::Item> {
-		self.as_mut().next()
+		self.as_mut().next_back()
 	}
 }
 
@@ -187,6 +739,42 @@ impl<T> ExactSizeIterator for ArenaBox<'_, T> where T: ExactSizeIterator + ?Size
 
 impl<T> FusedIterator for ArenaBox<'_, T> where T: FusedIterator + ?Sized { }
 
+impl<'b, T> IntoIterator for &'b ArenaBox<'_, [T]> {
+	type Item = &'b T;
+	type IntoIter = core::slice::Iter<'b, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.as_ref().iter()
+	}
+}
+
+// Through the blanket implementations in rayon, these give boxed slices ``par_iter`` and
+// ``par_iter_mut``.
+#[cfg(feature = "rayon")]
+impl<'b, T> rayon::iter::IntoParallelIterator for &'b ArenaBox<'_, [T]> where T: Sync + 'b {
+	type Item = &'b T;
+	type Iter = rayon::slice::Iter<'b, T>;
+
+	fn into_par_iter(self) -> Self::Iter {
+		self.as_ref().into_par_iter()
+	}
+}
+
+#[cfg(feature = "rayon")]
+impl<'b, T> rayon::iter::IntoParallelIterator for &'b mut ArenaBox<'_, [T]> where T: Send + 'b {
+	type Item = &'b mut T;
+	type Iter = rayon::slice::IterMut<'b, T>;
+
+	fn into_par_iter(self) -> Self::Iter {
+		self.as_mut().into_par_iter()
+	}
+}
+
+// There is no ``IntoIterator`` for ``&mut ArenaBox<[T]>``, because ``&mut I`` is an iterator
+// whenever ``I`` is, and ``ArenaBox<T>`` is an iterator whenever ``T`` is. The compiler can't rule
+// out that ``[T]`` becomes an iterator in the future, so the impls would overlap. Use
+// ``iter_mut`` instead.
+
 impl<T> Hash for ArenaBox<'_, T> where T: Hash + ?Sized {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.as_ref().hash(state);
@@ -238,6 +826,7 @@ impl<T> Hasher for ArenaBox<'_, T> where T: Hasher + ?Sized {
     }
 }
 
+#[cfg(feature = "std")]
 impl<R: Read + ?Sized> Read for ArenaBox<'_, R> {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -265,6 +854,7 @@ impl<R: Read + ?Sized> Read for ArenaBox<'_, R> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<W: Write + ?Sized> Write for ArenaBox<'_, W> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
@@ -292,6 +882,7 @@ impl<W: Write + ?Sized> Write for ArenaBox<'_, W> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<S: Seek + ?Sized> Seek for ArenaBox<'_, S> {
     #[inline]
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
@@ -299,6 +890,7 @@ impl<S: Seek + ?Sized> Seek for ArenaBox<'_, S> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> BufRead for ArenaBox<'_, T> where T: BufRead + ?Sized {
     #[inline]
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
@@ -323,36 +915,53 @@ impl<T> BufRead for ArenaBox<'_, T> where T: BufRead + ?Sized {
 
 impl<T: ?Sized> Unpin for ArenaBox<'_, T> {}
 
-impl<T: ?Sized + PartialEq> PartialEq for ArenaBox<'_, T> {
+impl<T: ?Sized + PartialEq<U>, U: ?Sized> PartialEq<ArenaBox<'_, U>> for ArenaBox<'_, T> {
     #[inline]
-    fn eq(&self, other: &ArenaBox<'_, T>) -> bool {
+    fn eq(&self, other: &ArenaBox<'_, U>) -> bool {
         PartialEq::eq(self.as_ref(), other.as_ref())
     }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U; N]> for ArenaBox<'_, [T]> {
+    #[inline]
+    fn eq(&self, other: &[U; N]) -> bool {
+        self.as_ref() == other
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<&[U]> for ArenaBox<'_, [T]> {
+    #[inline]
+    fn eq(&self, other: &&[U]) -> bool {
+        self.as_ref() == *other
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for ArenaBox<'_, [T]> {
     #[inline]
-    fn ne(&self, other: &ArenaBox<T>) -> bool {
-        PartialEq::ne(self.as_ref(), other.as_ref())
+    fn eq(&self, other: &Vec<U>) -> bool {
+        self.as_ref() == other.as_slice()
     }
 }
 
-impl<T: ?Sized + PartialOrd> PartialOrd for ArenaBox<'_, T> {
+impl<T: ?Sized + PartialOrd<U>, U: ?Sized> PartialOrd<ArenaBox<'_, U>> for ArenaBox<'_, T> {
     #[inline]
-    fn partial_cmp(&self, other: &ArenaBox<'_, T>) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &ArenaBox<'_, U>) -> Option<core::cmp::Ordering> {
         PartialOrd::partial_cmp(self.as_ref(), other.as_ref())
     }
     #[inline]
-    fn lt(&self, other: &ArenaBox<'_, T>) -> bool {
+    fn lt(&self, other: &ArenaBox<'_, U>) -> bool {
         PartialOrd::lt(self.as_ref(), other.as_ref())
     }
     #[inline]
-    fn le(&self, other: &ArenaBox<'_, T>) -> bool {
+    fn le(&self, other: &ArenaBox<'_, U>) -> bool {
         PartialOrd::le(self.as_ref(), other.as_ref())
     }
     #[inline]
-    fn ge(&self, other: &ArenaBox<'_, T>) -> bool {
+    fn ge(&self, other: &ArenaBox<'_, U>) -> bool {
         PartialOrd::ge(self.as_ref(), other.as_ref())
     }
     #[inline]
-    fn gt(&self, other: &ArenaBox<'_, T>) -> bool {
+    fn gt(&self, other: &ArenaBox<'_, U>) -> bool {
         PartialOrd::gt(self.as_ref(), other.as_ref())
     }
 }
@@ -0,0 +1,162 @@
+use core::alloc::Layout;
+use core::marker::PhantomData;
+
+use crate::{Arena, ArenaBox};
+
+/// Allocates items into both ends of an [Arena]. Items inserted at the front grow upwards from
+/// the start of the buffer, and items inserted at the back grow downwards from the end of it.
+///
+/// Allocation fails once the two ends meet, so the front and the back never overlap.
+///
+/// A forward [ArenaAlloc](crate::ArenaAlloc) and a [DoubleEndedAlloc] can't be used at the same
+/// time on the same [Arena], since both of them borrow it mutably. Use this one if you need both
+/// directions.
+pub struct DoubleEndedAlloc<'a> {
+	// INVARIANTS:
+	// * The buffer is allocated from ``front`` until ``back``, and lives for 'a.
+	// * ``front`` is never larger than ``back``.
+	front: *mut u8,
+	back: *mut u8,
+	_phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> DoubleEndedAlloc<'a> {
+	/// Creates an allocator that allocates from both ends of the buffer of an [Arena].
+	pub(crate) fn new(arena: &'a mut Arena) -> Self {
+		Self {
+			front: arena.buffer,
+			// SAFETY: The buffer is ``length`` bytes long, so this points one past the end of it.
+			back: unsafe { arena.buffer.add(arena.length) },
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Tries to allocate a space for T at the front and insert the value into it. If there isn't
+	/// enough space for T, it will return None.
+	#[inline]
+	pub fn try_insert_front<T>(&mut self, value: T) -> Option<ArenaBox<'a, T>> {
+		let ptr = self.try_alloc_front(Layout::new::<T>())? as *mut T;
+		// SAFETY: The pointer was just allocated, and nothing else can access it.
+		unsafe {
+			ptr.write(value);
+			Some(ArenaBox::from_raw(ptr))
+		}
+	}
+
+	/// Allocates a space for T at the front and inserts the value into it.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	#[inline]
+	pub fn insert_front<T>(&mut self, value: T) -> ArenaBox<'a, T> {
+		self.try_insert_front(value).expect("Arena ran out of space")
+	}
+
+	/// Tries to allocate a space for T at the back and insert the value into it. If there isn't
+	/// enough space for T, it will return None.
+	#[inline]
+	pub fn try_insert_back<T>(&mut self, value: T) -> Option<ArenaBox<'a, T>> {
+		let ptr = self.try_alloc_back(Layout::new::<T>())? as *mut T;
+		// SAFETY: The pointer was just allocated, and nothing else can access it.
+		unsafe {
+			ptr.write(value);
+			Some(ArenaBox::from_raw(ptr))
+		}
+	}
+
+	/// Allocates a space for T at the back and inserts the value into it.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	#[inline]
+	pub fn insert_back<T>(&mut self, value: T) -> ArenaBox<'a, T> {
+		self.try_insert_back(value).expect("Arena ran out of space")
+	}
+
+	/// Returns the number of bytes left between the front and the back.
+	pub fn remaining(&self) -> usize {
+		self.back as usize - self.front as usize
+	}
+
+	fn try_alloc_front(&mut self, layout: Layout) -> Option<*mut u8> {
+		if layout.size() == 0 {
+			return Some(layout.align() as *mut u8);
+		}
+
+		let front = self.front as usize;
+		let start = front.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+		let end = start.checked_add(layout.size())?;
+		if end > self.back as usize {
+			return None;
+		}
+
+		// SAFETY: start and end are within the buffer, and didn't overflow.
+		unsafe {
+			let value = self.front.add(start - front);
+			self.front = value.add(layout.size());
+			Some(value)
+		}
+	}
+
+	fn try_alloc_back(&mut self, layout: Layout) -> Option<*mut u8> {
+		if layout.size() == 0 {
+			return Some(layout.align() as *mut u8);
+		}
+
+		let back = self.back as usize;
+		let start = back.checked_sub(layout.size())? & !(layout.align() - 1);
+		if start < self.front as usize {
+			return None;
+		}
+
+		// SAFETY: start is within the buffer, and the value ends before the old back.
+		unsafe {
+			let value = self.back.sub(back - start);
+			self.back = value;
+			Some(value)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn both_ends() {
+		let mut arena = Arena::with_align(32, 8);
+		let mut alloc = arena.begin_double_ended();
+
+		let front = alloc.insert_front(1u64);
+		let back = alloc.insert_back(2u64);
+		assert!((front.as_ptr() as usize) < back.as_ptr() as usize);
+		assert_eq!(back.as_ptr() as usize % 8, 0);
+		assert_eq!(alloc.remaining(), 16);
+		assert_eq!((*front, *back), (1, 2));
+	}
+
+	#[test]
+	fn ends_meet() {
+		let mut arena = Arena::with_align(32, 4);
+		let mut alloc = arena.begin_double_ended();
+
+		let mut fronts = std::vec::Vec::new();
+		let mut backs = std::vec::Vec::new();
+		for i in 0..4u32 {
+			fronts.push(alloc.insert_front(i));
+			backs.push(alloc.insert_back(i + 100));
+		}
+		assert_eq!(alloc.remaining(), 0);
+		assert!(alloc.try_insert_front(0u8).is_none());
+		assert!(alloc.try_insert_back(0u8).is_none());
+
+		let highest_front = fronts.iter().map(|b| b.as_ptr() as usize).max().unwrap();
+		let lowest_back = backs.iter().map(|b| b.as_ptr() as usize).min().unwrap();
+		assert!(highest_front + 4 <= lowest_back);
+
+		for (i, (front, back)) in fronts.iter().zip(&backs).enumerate() {
+			assert_eq!(**front, i as u32);
+			assert_eq!(**back, i as u32 + 100);
+		}
+	}
+}
@@ -0,0 +1,155 @@
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use crate::ArenaAlloc;
+
+/// A temporary scope of allocations on top of an [ArenaAlloc]. Create one with
+/// [ArenaAlloc::frame].
+///
+/// The frame derefs to an allocator whose boxes borrow the allocator the frame came from. Once
+/// the frame is dropped, the memory they used is given back to that allocator, which can't be
+/// used until the boxes are gone. Unlike [ArenaAlloc::restore], this doesn't need any unsafe
+/// code, since the borrow checker makes sure the boxes don't alias later allocations.
+///
+/// ```
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let kept = alloc.insert(1u32);
+/// {
+///     let mut frame = alloc.frame();
+///     let temporary = frame.insert([0u8; 32]);
+///     assert_eq!(temporary.len(), 32);
+/// }
+/// assert_eq!(alloc.used(), 4);
+/// assert_eq!(*kept, 1);
+/// ```
+///
+/// Boxes from the frame keep the allocator borrowed, so it can't be used again while they are
+/// alive:
+/// ```compile_fail
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let escaped = {
+///     let mut frame = alloc.frame();
+///     frame.insert(1u32)
+/// };
+/// let other = alloc.insert(2u32);
+/// assert_eq!(*escaped, 1);
+/// ```
+pub struct Frame<'f, 'a> {
+	// INVARIANTS:
+	// * alloc allocates from where parent's head was when the frame was created, until the end of
+	//   the buffer, and parent isn't used until the frame is gone.
+	parent: &'f mut ArenaAlloc<'a>,
+	alloc: ArenaAlloc<'f>,
+}
+
+impl<'f, 'a> Frame<'f, 'a> {
+	/// Creates a frame that starts allocating where ``parent`` left off.
+	pub(crate) fn new(parent: &'f mut ArenaAlloc<'a>) -> Self {
+		let alloc = ArenaAlloc {
+			base: parent.base,
+			start: parent.head,
+			head: parent.head,
+			last: parent.last,
+			allocations: 0,
+			peak: 0,
+			arena_peak: None,
+			_phantom: PhantomData,
+		};
+		Self { parent, alloc }
+	}
+}
+
+impl<'f> Deref for Frame<'f, '_> {
+	type Target = ArenaAlloc<'f>;
+
+	fn deref(&self) -> &ArenaAlloc<'f> {
+		&self.alloc
+	}
+}
+
+impl<'f> DerefMut for Frame<'f, '_> {
+	fn deref_mut(&mut self) -> &mut ArenaAlloc<'f> {
+		&mut self.alloc
+	}
+}
+
+impl Drop for Frame<'_, '_> {
+	fn drop(&mut self) {
+		// The head of the parent never moved, so there's nothing to restore. The statistics still
+		// count what the frame did.
+		let stats = self.alloc.stats();
+		self.parent.allocations += stats.allocations;
+		self.parent.peak = self.parent.peak.max(self.parent.used() + stats.peak_bytes);
+		let reached = self.alloc.start as usize - self.alloc.base as usize + stats.peak_bytes;
+		if let Some(arena_peak) = self.parent.arena_peak.as_deref_mut() {
+			*arena_peak = (*arena_peak).max(reached);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Arena;
+
+	#[test]
+	fn nested() {
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let outer_value = alloc.insert(1u64);
+
+		{
+			let mut outer = alloc.frame();
+			let a = outer.insert(2u64);
+			let used = outer.used();
+			let reused;
+			{
+				let mut inner = outer.frame();
+				let b = inner.insert([3u8; 100]);
+				assert_eq!(inner.used(), 100);
+				assert_eq!(b[99], 3);
+				reused = b.as_ptr() as *const u8 as usize;
+			}
+			assert_eq!(outer.used(), used);
+			let c = outer.insert([4u8; 100]);
+			assert_eq!(c.as_ptr() as *const u8 as usize, reused);
+			assert_eq!(*a, 2);
+		}
+
+		assert_eq!(alloc.used(), 8);
+		assert_eq!(*outer_value, 1);
+		let stats = alloc.stats();
+		assert_eq!(stats.allocations, 4);
+		assert_eq!(stats.peak_bytes, 116);
+	}
+
+	#[test]
+	fn drops_values() {
+		use std::rc::Rc;
+
+		let counter = Rc::new(());
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		{
+			let mut frame = alloc.frame();
+			let _value = frame.insert(counter.clone());
+			assert_eq!(Rc::strong_count(&counter), 2);
+		}
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn region() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let before = alloc.insert(1u32);
+		let mut frame = alloc.frame();
+		assert_eq!(frame.used(), 0);
+		assert_eq!(frame.capacity(), 60);
+		assert!(!frame.contains(&before));
+
+		let inside = frame.insert(2u32);
+		assert!(frame.contains(&inside));
+	}
+}
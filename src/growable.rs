@@ -0,0 +1,236 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::mem::MaybeUninit;
+
+use crate::{Arena, ArenaAlloc, ArenaBox};
+
+/// An arena made out of several [Arena]s, that allocates a new, larger, [Arena] whenever the
+/// current one runs out of space.
+///
+/// This is useful when you can't predict how much memory you need up front. The blocks are kept
+/// around, so later batches of allocations can reuse them.
+pub struct GrowableArena {
+	// INVARIANTS:
+	// * There is always at least one block.
+	blocks: Vec<Arena>,
+}
+
+impl GrowableArena {
+	/// Creates a new growable arena, where the first block has the specified length.
+	///
+	/// # Panics
+	/// * If the given length is 0.
+	///
+	/// If the allocation fails, [handle_alloc_error](alloc::alloc::handle_alloc_error) is called,
+	/// which usually aborts.
+	pub fn new(length: usize) -> Self {
+		Self {
+			blocks: vec![Arena::new(length)],
+		}
+	}
+
+	/// Returns the total number of bytes in all the blocks.
+	pub fn capacity(&self) -> usize {
+		self.blocks.iter().map(|block| block.length).sum()
+	}
+
+	/// Allows allocating elements, starting from the first block.
+	///
+	/// Like [Arena::begin_alloc], this can be called multiple times to reuse the same blocks for
+	/// several batches of allocations.
+	pub fn begin_alloc<'a>(&'a mut self) -> GrowableAlloc<'a> {
+		let first = &self.blocks[0];
+		// SAFETY: The arena is borrowed mutably for 'a, so nothing else can use the buffer.
+		let current = unsafe { ArenaAlloc::from_buffer(first.buffer, first.length, 0) };
+
+		GrowableAlloc {
+			blocks: &mut self.blocks,
+			index: 0,
+			current,
+		}
+	}
+}
+
+/// Allocates items into a [GrowableArena].
+pub struct GrowableAlloc<'a> {
+	// INVARIANTS:
+	// * current allocates from the block at index.
+	// * Blocks are never removed or deallocated while this exists, only added.
+	blocks: &'a mut Vec<Arena>,
+	index: usize,
+	current: ArenaAlloc<'a>,
+}
+
+impl<'a> GrowableAlloc<'a> {
+	/// Tries to allocate a space for T and insert the value into it. If there isn't enough space
+	/// for T, and a new block can't be allocated, it will return None.
+	#[inline]
+	pub fn try_insert<T>(&mut self, value: T) -> Option<ArenaBox<'a, T>> {
+		self.try_insert_with(|| value)
+	}
+
+	/// Allocates a space for T and inserts the value into it.
+	///
+	/// # Panics
+	/// * If a new block is needed but can't be allocated.
+	#[inline]
+	pub fn insert<T>(&mut self, value: T) -> ArenaBox<'a, T> {
+		self.insert_with(|| value)
+	}
+
+	/// Tries to allocate a space for T and insert the value the function returns into it.
+	/// If there isn't enough space for T, and a new block can't be allocated, it will return
+	/// None.
+	#[inline]
+	pub fn try_insert_with<F, T>(&mut self, value: F) -> Option<ArenaBox<'a, T>>
+		where F: FnOnce() -> T
+	{
+		let mut slot = self.try_alloc_uninit::<T>()?;
+		slot.as_mut().write(value());
+		// SAFETY: We just wrote the value.
+		unsafe { Some(slot.assume_init()) }
+	}
+
+	/// Allocates a space for T and inserts the value the function returns into it.
+	///
+	/// # Panics
+	/// * If a new block is needed but can't be allocated.
+	#[inline]
+	pub fn insert_with<F, T>(&mut self, value: F) -> ArenaBox<'a, T>
+		where F: FnOnce() -> T
+	{
+		self.try_insert_with(value).expect("Allocation failed")
+	}
+
+	/// Allocates the space for and inserts a slice. Returns None if there is not enough space,
+	/// and a new block can't be allocated.
+	pub fn try_insert_slice<T: Copy>(&mut self, slice: &[T]) -> Option<ArenaBox<'a, [T]>> {
+		let layout = Layout::array::<T>(slice.len()).ok()?;
+		let buffer = self.try_alloc_layout(layout)? as *mut T;
+
+		// SAFETY: We know that buffer is valid for slice.len() elements, and that it doesn't
+		// overlap with slice.
+		unsafe {
+			core::ptr::copy_nonoverlapping(slice.as_ptr(), buffer, slice.len());
+			Some(ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(buffer, slice.len())))
+		}
+	}
+
+	/// Allocates the space for and inserts a slice.
+	///
+	/// # Panics
+	/// * If a new block is needed but can't be allocated.
+	pub fn insert_slice<T: Copy>(&mut self, slice: &[T]) -> ArenaBox<'a, [T]> {
+		self.try_insert_slice(slice).expect("Allocation failed")
+	}
+
+	/// Allocates the space for and inserts a string. Returns None if there is not enough space,
+	/// and a new block can't be allocated.
+	pub fn try_insert_str(&mut self, string: &str) -> Option<ArenaBox<'a, str>> {
+		let bytes = self.try_insert_slice(string.as_bytes())?.leak();
+		// SAFETY: The bytes were copied from a str, so they are valid utf-8.
+		unsafe { Some(ArenaBox::from_raw(core::str::from_utf8_unchecked_mut(bytes))) }
+	}
+
+	/// Allocates the space for and inserts a string.
+	///
+	/// # Panics
+	/// * If a new block is needed but can't be allocated.
+	pub fn insert_str(&mut self, string: &str) -> ArenaBox<'a, str> {
+		self.try_insert_str(string).expect("Allocation failed")
+	}
+
+	/// Tries to allocate space for a T, without initializing it. If there isn't enough space, and
+	/// a new block can't be allocated, it will return None.
+	pub fn try_alloc_uninit<T>(&mut self) -> Option<ArenaBox<'a, MaybeUninit<T>>> {
+		let ptr = self.try_alloc_layout(Layout::new::<T>())? as *mut MaybeUninit<T>;
+		// SAFETY: A MaybeUninit doesn't have to be initialized, and the pointer was just allocated.
+		unsafe { Some(ArenaBox::from_raw(ptr)) }
+	}
+
+	fn try_alloc_layout(&mut self, layout: Layout) -> Option<*mut u8> {
+		if let Some(ptr) = self.current.try_alloc_layout(layout) {
+			return Some(ptr);
+		}
+
+		// Move on to the next block that is large enough, or allocate a new one if there isn't
+		// one. The worst case padding is one less than the alignment.
+		// Nothing is changed until the block is known to exist, so a failed allocation keeps
+		// allocating from the current one.
+		let needed = layout.size().checked_add(layout.align() - 1)?;
+		let mut index = self.index + 1;
+		while index < self.blocks.len() && self.blocks[index].length < needed {
+			index += 1;
+		}
+
+		if index == self.blocks.len() {
+			let last = self.blocks.last().unwrap().length;
+			let length = last.saturating_mul(2).max(needed);
+			self.blocks.push(Arena::try_new(length).ok()?);
+		}
+
+		let block = &self.blocks[index];
+		// SAFETY: The arena is borrowed mutably for 'a, and the blocks are never deallocated while
+		// the allocator exists. The blocks after index have not been handed out yet.
+		self.current = unsafe { ArenaAlloc::from_buffer(block.buffer, block.length, 0) };
+		self.index = index;
+		self.current.try_alloc_layout(layout)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::rc::Rc;
+
+	#[test]
+	fn grow() {
+		let counter = Rc::new(());
+		let mut arena = GrowableArena::new(64);
+
+		{
+			let mut alloc = arena.begin_alloc();
+			let boxes: Vec<_> = (0..100u64).map(|i| alloc.insert((i, counter.clone()))).collect();
+			assert_eq!(Rc::strong_count(&counter), 101);
+
+			for (i, value) in boxes.iter().enumerate() {
+				assert_eq!(value.0, i as u64);
+			}
+		}
+
+		assert_eq!(Rc::strong_count(&counter), 1);
+		assert!(arena.blocks.len() > 1);
+		assert!(arena.capacity() >= 100 * std::mem::size_of::<(u64, Rc<()>)>());
+	}
+
+	#[test]
+	fn reuse_blocks() {
+		let mut arena = GrowableArena::new(16);
+		arena.begin_alloc().insert_slice(&[0u8; 1000]);
+		let blocks = arena.blocks.len();
+
+		{
+			let mut alloc = arena.begin_alloc();
+			let string = alloc.insert_str("Hello");
+			let slice = alloc.insert_slice(&[1u8; 1000]);
+			assert_eq!(&*string, "Hello");
+			assert!(slice.iter().all(|&v| v == 1));
+		}
+
+		assert_eq!(arena.blocks.len(), blocks);
+	}
+
+	#[test]
+	fn failed_growth() {
+		let mut arena = GrowableArena::new(16);
+		let mut alloc = arena.begin_alloc();
+		let first = alloc.insert([1u8; 8]);
+		// No allocator can hand out this much, so the new block can't be allocated.
+		assert!(alloc.try_alloc_uninit::<[u8; 1 << 60]>().is_none());
+
+		let second = alloc.try_insert([2u8; 12]).unwrap();
+		let third = alloc.insert([3u8; 4]);
+		assert_eq!((*first, *second, *third), ([1; 8], [2; 12], [3; 4]));
+	}
+}
@@ -0,0 +1,131 @@
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+/// An iterator that moves the elements out of an [ArenaBox](crate::ArenaBox)ed slice, see
+/// [ArenaBox::into_iter](crate::ArenaBox::into_iter).
+///
+/// Any elements that haven't been yielded when the iterator is dropped are dropped with it.
+pub struct ArenaBoxIntoIter<'a, T> {
+	// INVARIANTS:
+	// * The len elements starting at start are valid, and nothing else accesses them for 'a.
+	start: *mut T,
+	len: usize,
+	_phantom: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> ArenaBoxIntoIter<'a, T> {
+	/// # Safety
+	/// * The ``len`` elements starting at ``start`` have to be valid, and nothing else may access
+	///   or drop them for 'a.
+	pub(crate) unsafe fn new(start: *mut T, len: usize) -> Self {
+		Self {
+			start,
+			len,
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Returns the elements that haven't been yielded yet.
+	pub fn as_slice(&self) -> &[T] {
+		// SAFETY: See the invariants.
+		unsafe { core::slice::from_raw_parts(self.start, self.len) }
+	}
+
+	/// Returns the elements that haven't been yielded yet, mutably.
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		// SAFETY: See the invariants.
+		unsafe { core::slice::from_raw_parts_mut(self.start, self.len) }
+	}
+}
+
+impl<T> Iterator for ArenaBoxIntoIter<'_, T> {
+	type Item = T;
+
+	fn next(&mut self) -> Option<T> {
+		if self.len == 0 {
+			return None;
+		}
+
+		// SAFETY: There is at least one valid element left, and it's removed from the iterator
+		// after it is read, so it is not read or dropped again.
+		unsafe {
+			let value = self.start.read();
+			self.start = self.start.add(1);
+			self.len -= 1;
+			Some(value)
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<T> DoubleEndedIterator for ArenaBoxIntoIter<'_, T> {
+	fn next_back(&mut self) -> Option<T> {
+		if self.len == 0 {
+			return None;
+		}
+
+		// SAFETY: Same as for next.
+		unsafe {
+			self.len -= 1;
+			Some(self.start.add(self.len).read())
+		}
+	}
+}
+
+impl<T> ExactSizeIterator for ArenaBoxIntoIter<'_, T> {}
+
+impl<T> FusedIterator for ArenaBoxIntoIter<'_, T> {}
+
+impl<T> Drop for ArenaBoxIntoIter<'_, T> {
+	fn drop(&mut self) {
+		// SAFETY: The remaining elements are valid, and haven't been yielded.
+		unsafe {
+			core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(self.start, self.len));
+		}
+	}
+}
+
+// SAFETY: The iterator owns the remaining elements, like the ArenaBox it came from.
+unsafe impl<T: Send> Send for ArenaBoxIntoIter<'_, T> {}
+unsafe impl<T: Sync> Sync for ArenaBoxIntoIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+	use crate::Arena;
+	use std::rc::Rc;
+	use std::string::{String, ToString};
+	use std::vec::Vec;
+
+	#[test]
+	fn drain() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let strings = alloc.insert_all((0..4).map(|v| v.to_string()));
+		let strings: Vec<String> = strings.into_iter().collect();
+		assert_eq!(strings, ["0", "1", "2", "3"]);
+
+		let numbers = alloc.insert_slice(&[1, 2, 3]);
+		assert!(numbers.into_iter().rev().eq([3, 2, 1]));
+	}
+
+	#[test]
+	fn partial_drain() {
+		let counter = Rc::new(());
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let counters = alloc.insert_all((0..4).map(|_| counter.clone()));
+
+		let mut iter = counters.into_iter();
+		assert_eq!(iter.len(), 4);
+		drop(iter.next());
+		drop(iter.next_back());
+		assert_eq!(iter.as_slice().len(), 2);
+		assert_eq!(Rc::strong_count(&counter), 3);
+
+		drop(iter);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+}
@@ -16,21 +16,122 @@
 //! An [ArenaBox] works exactly like a [Box] except it has a lifetime, and it drops the thing it
 //! contains.
 //!
-#[warn(missing_docs)]
+//! The crate is ``no_std``, it only needs the ``alloc`` crate. The ``std`` feature, which is on
+//! by default, adds the implementations of the ``std::io`` traits for [ArenaBox], the
+//! [ArenaReader], and the thread local scratch arenas of [with_scratch]. The optional ``serde``
+//! feature implements ``Serialize`` for [ArenaBox], and adds ``ArenaSeed`` to deserialize values
+//! straight into an allocator. The optional ``bytemuck`` feature adds ``ArenaBox::cast_slice``,
+//! which reinterprets a boxed slice of plain old data as another type, and the optional ``rayon``
+//! feature lets boxed slices be iterated over in parallel.
+//!
+//! [Box]: alloc::boxed::Box
+#![no_std]
+#![warn(missing_docs)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+#![cfg_attr(feature = "nightly", feature(coerce_unsized, unsize))]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
 
-use std::alloc::{alloc, dealloc, Layout};
-use std::marker::PhantomData;
-use std::ptr::NonNull;
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Range;
+use core::mem::{self, MaybeUninit};
 
 mod r#box;
 pub use r#box::ArenaBox;
 
+mod iter;
+pub use iter::ArenaBoxIntoIter;
+
+mod double_ended;
+pub use double_ended::DoubleEndedAlloc;
+
+mod frame;
+pub use frame::Frame;
+
+mod growable;
+pub use growable::{GrowableArena, GrowableAlloc};
+
+mod string;
+pub use string::ArenaString;
+
+mod tracked;
+pub use tracked::TrackedAlloc;
+
+mod vec;
+pub use vec::ArenaVec;
+
+mod writer;
+pub use writer::ArenaStringWriter;
+
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "std")]
+pub use reader::ArenaReader;
+
+#[cfg(feature = "std")]
+mod scratch;
+#[cfg(feature = "std")]
+pub use scratch::with_scratch;
+
+#[cfg(feature = "serde")]
+mod seed;
+#[cfg(feature = "serde")]
+pub use seed::ArenaSeed;
+
+#[cfg(feature = "allocator_api")]
+mod shared;
+#[cfg(feature = "allocator_api")]
+pub use shared::{SharedArena, SharedArenaAlloc};
+
+/// Collects an iterator into a boxed slice in an allocator, for call sites that read better
+/// with the iterator last. See [ArenaAlloc::insert_all].
+///
+/// # Panics
+/// * If the elements do not fit.
+///
+/// ```
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let squares = arena::collect_in(&mut alloc, (1..5u32).map(|x| x * x));
+/// assert_eq!(&*squares, &[1, 4, 9, 16]);
+/// let evens = alloc.collect_slice(squares.iter().copied().filter(|x| x % 2 == 0));
+/// assert_eq!(&*evens, &[4, 16]);
+/// assert!(alloc.try_collect(0..100u64).is_none());
+/// ```
+pub fn collect_in<'a, T, I>(alloc: &mut ArenaAlloc<'a>, items: I) -> ArenaBox<'a, [T]>
+	where I: IntoIterator<Item = T>
+{
+	alloc.insert_all(items)
+}
+
 /// A buffer that contains heap allocated memory that can be used by the [ArenaAlloc].
+///
+/// An arena can be moved to another thread, for example to hand it to a worker:
+/// ```
+/// let mut arena = arena::Arena::new(64);
+/// std::thread::spawn(move || {
+///     let mut alloc = arena.begin_alloc();
+///     assert_eq!(*alloc.insert(5), 5);
+/// }).join().unwrap();
+/// ```
 pub struct Arena {
 	// INVARIANTS:
-	// * buffer is an allocated block of memory with length bytes.
+	// * buffer is an allocated block of memory with length bytes, aligned to align.
+	// * If block is None, the arena allocated the buffer itself, with a layout of length and
+	//   align. Otherwise the buffer is a part of the block, which deallocates it once every arena
+	//   that shares it has been dropped.
+	// * peak is the most bytes of the buffer any batch of allocations has used so far.
 	buffer: *mut u8,
 	length: usize,
+	align: usize,
+	block: Option<Arc<SharedBlock>>,
+	peak: usize,
 }
 
 impl Arena {
@@ -42,18 +143,233 @@ impl Arena {
 	///
 	/// # Panics
 	/// * If the given length is 0.
-	/// * If the allocation fails.
+	/// * If the length is too large to ever be allocated.
+	///
+	/// If the allocation fails, [handle_alloc_error] is called, which usually aborts. Use
+	/// [Arena::try_new] to handle the failure instead.
 	pub fn new(length: usize) -> Self {
-		assert!(length > 0, "length cannot be zero");
+		Self::with_align_or_handle(length, 1)
+	}
 
-		// SAFETY: We know length is larger than zero.
-		let buffer = unsafe { alloc(Layout::from_size_align(length, 1).unwrap()) };
-		assert!(!buffer.is_null(), "Allocation failed");
+	/// Allocates a new arena with the specified length, like [Arena::new], but returns an error
+	/// instead of panicking.
+	pub fn try_new(length: usize) -> Result<Self, ArenaError> {
+		Self::try_with_align(length, 1)
+	}
+
+	/// Allocates a new arena with the specified length, where the start of the buffer is aligned
+	/// to ``align``.
+	///
+	/// If you store a lot of values with a large alignment, aligning the buffer to that alignment
+	/// means the first value will never need any padding, and values of the same type following
+	/// it won't either.
+	///
+	/// # Panics
+	/// * If the given length is 0.
+	/// * If ``align`` is not a power of two.
+	/// * If the length is too large to ever be allocated.
+	///
+	/// If the allocation fails, [handle_alloc_error] is called, which usually aborts.
+	pub fn with_align(length: usize, align: usize) -> Self {
+		assert!(align.is_power_of_two(), "align has to be a power of two");
+		Self::with_align_or_handle(length, align)
+	}
+
+	/// Allocates a new arena where the start of the buffer is aligned to a page, and the length
+	/// is rounded up to a whole number of pages. See [Arena::page_size].
+	///
+	/// # Panics
+	/// * If the given length is 0.
+	/// * If the length is too large to ever be allocated.
+	///
+	/// If the allocation fails, [handle_alloc_error] is called, which usually aborts.
+	pub fn new_page_aligned(length: usize) -> Self {
+		assert!(length != 0, "length cannot be zero");
 
+		let page_size = Self::page_size();
+		let rounded = length.checked_add(page_size - 1).expect("Allocation failed");
+		let rounded = rounded & !(page_size - 1);
+		Self::with_align_or_handle(rounded, page_size)
+	}
+
+	/// Returns the size of a memory page, which [Arena::new_page_aligned] aligns to.
+	///
+	/// This is asked from the operating system on Linux and macOS when the ``std`` feature is
+	/// enabled, since that's when the C library is linked. Otherwise, it's assumed to be 4096
+	/// bytes.
+	pub fn page_size() -> usize {
+		#[cfg(all(feature = "std", any(target_os = "linux", target_os = "macos")))]
+		{
+			extern "C" {
+				fn sysconf(name: core::ffi::c_int) -> core::ffi::c_long;
+			}
+
+			// The value of _SC_PAGESIZE differs between C libraries. On Linux it's 30, from
+			// bits/confname.h in glibc, which musl uses too. On macOS it's 29, from <unistd.h>.
+			#[cfg(target_os = "linux")]
+			const SC_PAGESIZE: core::ffi::c_int = 30;
+			#[cfg(target_os = "macos")]
+			const SC_PAGESIZE: core::ffi::c_int = 29;
+
+			// SAFETY: sysconf doesn't have any preconditions, and returns -1 on failure.
+			let size = unsafe { sysconf(SC_PAGESIZE) };
+			if size > 0 && (size as usize).is_power_of_two() {
+				return size as usize;
+			}
+		}
+
+		4096
+	}
+
+	/// Creates an arena that uses the allocation of a [Vec] as its buffer, so no new allocation
+	/// is made. The whole capacity of the vector is used, its contents are ignored.
+	///
+	/// # Panics
+	/// * If the capacity of the vector is 0.
+	pub fn from_vec(buffer: Vec<u8>) -> Self {
+		assert!(buffer.capacity() > 0, "length cannot be zero");
+
+		let mut buffer = core::mem::ManuallyDrop::new(buffer);
+		// A Vec<u8> allocates its buffer with the global allocator, with a size of its capacity and
+		// an alignment of 1. That's the exact same layout as an arena with an alignment of 1, so
+		// the arena can deallocate it just like any other buffer.
 		Self {
+			buffer: buffer.as_mut_ptr(),
+			length: buffer.capacity(),
+			align: 1,
+			block: None,
+			peak: 0,
+		}
+	}
+
+	/// Converts the arena back into a [Vec], so that the buffer can be reused for something else.
+	///
+	/// The vector is empty, with a capacity of the length of the arena. It can't contain the old
+	/// contents of the arena, because parts of the buffer may never have been initialized.
+	///
+	/// Any [ArenaBox] still pointing into the arena would be dangling after this, but since this
+	/// takes the arena by value, the borrow checker makes sure there can't be any.
+	///
+	/// If the arena was created with an alignment other than 1, or was split with
+	/// [Arena::split_at], the buffer can't be given to a [Vec], so a new vector is allocated
+	/// instead.
+	pub fn into_vec(self) -> Vec<u8> {
+		if self.align != 1 || self.block.is_some() {
+			let length = self.length;
+			drop(self);
+			return Vec::with_capacity(length);
+		}
+
+		let arena = core::mem::ManuallyDrop::new(self);
+		// SAFETY: The buffer was allocated with the global allocator with a size of length and an
+		// alignment of 1, which is the layout a Vec<u8> with a capacity of length would have.
+		// The arena is never dropped, so the buffer is not deallocated twice.
+		unsafe { Vec::from_raw_parts(arena.buffer, 0, arena.length) }
+	}
+
+	/// Resizes the buffer to ``new_length`` bytes, between batches of allocations. The old
+	/// contents of the buffer can't be relied on afterwards.
+	///
+	/// Any [ArenaBox] still pointing into the arena would be dangling after this, but since this
+	/// borrows the arena mutably, the borrow checker makes sure there can't be any.
+	///
+	/// If the arena was split with [Arena::split_at], it stops sharing the block it was split
+	/// from, and gets a buffer of its own.
+	///
+	/// # Panics
+	/// * If ``new_length`` is less than the current length, use [Arena::shrink] for that.
+	/// * If the new length is too large to ever be allocated.
+	///
+	/// If the allocation fails, [handle_alloc_error] is called, which usually aborts.
+	pub fn grow(&mut self, new_length: usize) {
+		assert!(new_length >= self.length, "new length is less than the current length");
+		self.reallocate(new_length);
+	}
+
+	/// Resizes the buffer to ``new_length`` bytes, between batches of allocations, to give back
+	/// memory when the arena is larger than it needs to be. Like [Arena::grow], the old contents
+	/// of the buffer can't be relied on afterwards, and a split arena gets a buffer of its own.
+	///
+	/// # Panics
+	/// * If ``new_length`` is 0, or larger than the current length.
+	///
+	/// If the allocation fails, [handle_alloc_error] is called, which usually aborts.
+	pub fn shrink(&mut self, new_length: usize) {
+		assert!(new_length <= self.length, "new length is larger than the current length");
+		assert!(new_length > 0, "length cannot be zero");
+		self.reallocate(new_length);
+	}
+
+	/// Resizes the buffer to ``new_length`` bytes, with the same alignment. A split arena gets a
+	/// new buffer of its own and gives back its part of the shared block.
+	fn reallocate(&mut self, new_length: usize) {
+		// The peak is kept either way, since it's still useful for deciding how large the arena
+		// should be.
+		if self.block.is_some() {
+			// The part of the block can't be resized, or given back on its own, so a new buffer is
+			// needed even if the length stays the same. The old arena is dropped when it's
+			// replaced, which lets go of the block.
+			let peak = self.peak;
+			*self = Self::with_align_or_handle(new_length, self.align);
+			self.peak = peak;
+			return;
+		}
+
+		if new_length == self.length {
+			return;
+		}
+
+		let new_layout = match Layout::from_size_align(new_length, self.align) {
+			Ok(layout) => layout,
+			Err(_) => panic!("Allocation failed"),
+		};
+		// SAFETY: See the invariant, the arena allocated the buffer itself with a layout of length
+		// and align. The callers make sure new_length isn't zero, and the layout above makes sure
+		// it doesn't overflow when rounded up to align.
+		let buffer = unsafe {
+			let old_layout = Layout::from_size_align(self.length, self.align).unwrap();
+			realloc(self.buffer, old_layout, new_length)
+		};
+		if buffer.is_null() {
+			// The old buffer is still valid, so the arena is left as it was.
+			handle_alloc_error(new_layout);
+		}
+		self.buffer = buffer;
+		self.length = new_length;
+	}
+
+	/// Like [Arena::try_with_align], but reports running out of memory the same way the standard
+	/// library does.
+	fn with_align_or_handle(length: usize, align: usize) -> Self {
+		match Self::try_with_align(length, align) {
+			Ok(arena) => arena,
+			Err(ArenaError::ZeroLength) => panic!("length cannot be zero"),
+			Err(ArenaError::AllocFailed) => match Layout::from_size_align(length, align) {
+				Ok(layout) => handle_alloc_error(layout),
+				Err(_) => panic!("Allocation failed"),
+			},
+		}
+	}
+
+	fn try_with_align(length: usize, align: usize) -> Result<Self, ArenaError> {
+		if length == 0 {
+			return Err(ArenaError::ZeroLength);
+		}
+
+		let layout = Layout::from_size_align(length, align).map_err(|_| ArenaError::AllocFailed)?;
+		// SAFETY: We know length is larger than zero.
+		let buffer = unsafe { alloc(layout) };
+		if buffer.is_null() {
+			return Err(ArenaError::AllocFailed);
+		}
+
+		Ok(Self {
 			buffer,
 			length,
-		}
+			align,
+			block: None,
+			peak: 0,
+		})
 	}
 
 	/// Allows allocating elements from the start of the buffer.
@@ -62,73 +378,465 @@ impl Arena {
 	/// to reuse the same buffer for several batches of allocations, however, it is statically
 	/// guaranteed that no allocations from one batch can live to the next batch.
 	pub fn begin_alloc<'a>(&'a mut self) -> ArenaAlloc<'a> {
-		ArenaAlloc {
-			head: self.buffer,
-			// SAFETY: Because self.buffer is an allocation of self.length elements,
-			// self.length - 1 will never overflow. self.length is also larger than zero,
-			// which means
-			last: unsafe { self.buffer.add(self.length - 1) },
-			_phantom: PhantomData,
+		self.begin_alloc_from(0)
+	}
+
+	/// Begins a batch of allocations ``offset`` bytes into the buffer, which reports how much it
+	/// uses to [Arena::peak_bytes].
+	///
+	/// # Panics
+	/// * If ``offset`` is larger than the length of the arena.
+	fn begin_alloc_from(&mut self, offset: usize) -> ArenaAlloc<'_> {
+		assert!(offset <= self.length, "offset is out of bounds");
+		// SAFETY: The arena is borrowed mutably for 'a, so nothing else can use the buffer, and
+		// the offset is inside of it.
+		let mut alloc = unsafe { ArenaAlloc::from_buffer(self.buffer, self.length, offset) };
+		alloc.arena_peak = Some(&mut self.peak);
+		alloc
+	}
+
+	/// Returns the most bytes any batch of allocations from this arena has used, including
+	/// padding and, for [Arena::begin_alloc_at], the bytes before the offset. Unlike
+	/// [Stats::peak_bytes], this doesn't start over with every batch, and it counts the space
+	/// inserts reserve ahead of time, since the arena needed room for it.
+	///
+	/// This is useful for deciding how large the arena should be, after running it a few times.
+	///
+	/// ```
+	/// let mut arena = arena::Arena::new(1024);
+	/// for len in [10, 200, 50] {
+	///     let mut alloc = arena.begin_alloc();
+	///     let _ = alloc.insert_all(0..len as u8);
+	/// }
+	/// assert_eq!(arena.peak_bytes(), 200);
+	/// ```
+	pub fn peak_bytes(&self) -> usize {
+		self.peak
+	}
+
+	/// Splits the arena into two arenas, where the first one uses the bytes in ``[0, mid)`` and
+	/// the second one the bytes in ``[mid, length)``. Each of them can be used, and sent to
+	/// another thread, on its own.
+	///
+	/// The buffer is deallocated once both of the arenas have been dropped.
+	///
+	/// # Panics
+	/// * If either of the arenas would be empty, in other words if ``mid`` is 0, or not less
+	///   than the length.
+	pub fn split_at(self, mid: usize) -> (Arena, Arena) {
+		assert!(0 < mid && mid < self.length, "mid is out of bounds");
+
+		let arena = mem::ManuallyDrop::new(self);
+		// The arena is not dropped, so the block it shares, or the buffer it owns, is moved to
+		// the halves.
+		let block = match &arena.block {
+			// SAFETY: The block is read exactly once, and the arena is never dropped.
+			Some(block) => unsafe { core::ptr::read(block) },
+			None => Arc::new(SharedBlock {
+				buffer: arena.buffer,
+				layout: Layout::from_size_align(arena.length, arena.align).unwrap(),
+			}),
+		};
+
+		let first = Arena {
+			buffer: arena.buffer,
+			length: mid,
+			align: arena.align,
+			block: Some(block.clone()),
+			peak: 0,
+		};
+		let second = Arena {
+			// SAFETY: mid is inside the buffer.
+			buffer: unsafe { arena.buffer.add(mid) },
+			length: arena.length - mid,
+			// The second half is only aligned to the largest power of two that divides mid.
+			align: arena.align.min(1 << mid.trailing_zeros()),
+			block: Some(block),
+			peak: 0,
+		};
+		(first, second)
+	}
+
+	/// Returns the range of addresses the buffer covers, for checking whether a pointer points
+	/// into the arena.
+	pub fn as_ptr_range(&self) -> Range<*const u8> {
+		let start = self.buffer as *const u8;
+		start..start.wrapping_add(self.length)
+	}
+
+	/// Returns how many values of T are guaranteed to fit in a fresh batch of allocations, even
+	/// with the most padding the start of the buffer could need to align a T. Zero sized types
+	/// always fit, so for them this returns ``usize::MAX``.
+	///
+	/// ```
+	/// let arena = arena::Arena::new(64);
+	/// assert_eq!(arena.capacity_for::<u8>(), 64);
+	/// assert!(arena.capacity_for::<u64>() >= 7);
+	/// ```
+	pub fn capacity_for<T>(&self) -> usize {
+		let size = mem::size_of::<T>();
+		if size == 0 {
+			return usize::MAX;
+		}
+
+		// The buffer is aligned to self.align, so that much of the alignment of T is already
+		// taken care of.
+		let padding = mem::align_of::<T>().saturating_sub(self.align);
+		self.length.saturating_sub(padding) / size
+	}
+
+	/// Overwrites the whole buffer with zeroes, so that nothing from the previous batches of
+	/// allocations is left in memory. The next call to [Arena::begin_alloc] starts from cleared
+	/// memory.
+	pub fn reset_zeroed(&mut self) {
+		// SAFETY: The arena is borrowed mutably, so no boxes into the buffer are alive, and the
+		// buffer is ``length`` bytes long.
+		unsafe { core::ptr::write_bytes(self.buffer, 0, self.length) }
+	}
+
+	/// Allows allocating elements, starting ``offset`` bytes into the buffer. The bytes before
+	/// that are left alone, so they can be used for data you manage yourself, through
+	/// [Arena::as_mut_ptr].
+	///
+	/// # Panics
+	/// * If ``offset`` is not less than the length of the arena.
+	pub fn begin_alloc_at<'a>(&'a mut self, offset: usize) -> ArenaAlloc<'a> {
+		assert!(offset < self.length, "offset is out of bounds");
+		self.begin_alloc_from(offset)
+	}
+
+	/// Returns a pointer to the start of the buffer.
+	///
+	/// The pointer stays valid for as long as the arena, but writing through it while an
+	/// [ArenaBox] points to the same bytes is undefined behaviour. It's meant for the bytes that
+	/// [Arena::begin_alloc_at] leaves alone.
+	pub fn as_mut_ptr(&mut self) -> *mut u8 {
+		self.buffer
+	}
+
+	/// Like [Arena::begin_alloc], but returns an allocator that drops everything that was
+	/// inserted when it is dropped itself, see [TrackedAlloc].
+	pub fn begin_alloc_tracked<'a>(&'a mut self) -> TrackedAlloc<'a> {
+		TrackedAlloc::new(self.begin_alloc())
+	}
+
+	/// Overwrites the whole buffer with zeroes, like [Arena::reset_zeroed], but with volatile
+	/// writes, so the compiler can't optimize the clearing away even if it can tell the buffer is
+	/// never read again. Use this for wiping secrets like keys.
+	pub fn zeroize(&mut self) {
+		for i in 0..self.length {
+			// SAFETY: The arena is borrowed mutably, so no boxes into the buffer are alive, and
+			// the buffer is ``length`` bytes long.
+			unsafe { core::ptr::write_volatile(self.buffer.add(i), 0) }
 		}
+		// Makes sure later code isn't moved in front of the clearing.
+		core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+	}
+
+	/// Allows allocating elements from both ends of the buffer, see [DoubleEndedAlloc].
+	///
+	/// Like [Arena::begin_alloc], this resets the buffer, so it can be called multiple times.
+	pub fn begin_double_ended<'a>(&'a mut self) -> DoubleEndedAlloc<'a> {
+		DoubleEndedAlloc::new(self)
+	}
+
+	/// Begins a batch of allocations, runs ``f`` with it, and ends the batch before returning.
+	///
+	/// The result of ``f`` can't borrow from the allocator, so no [ArenaBox]es can escape the
+	/// batch, and you don't have to worry about dropping them before the next batch.
+	///
+	/// ```
+	/// use arena::{Arena, ArenaAlloc, ArenaBox};
+	///
+	/// enum Expr<'a> {
+	///     Number(i64),
+	///     Add(ArenaBox<'a, Expr<'a>>, ArenaBox<'a, Expr<'a>>),
+	/// }
+	///
+	/// fn parse<'a>(alloc: &mut ArenaAlloc<'a>, numbers: &[i64]) -> ArenaBox<'a, Expr<'a>> {
+	///     match numbers {
+	///         [number] => alloc.insert(Expr::Number(*number)),
+	///         [number, rest @ ..] => {
+	///             let left = alloc.insert(Expr::Number(*number));
+	///             let right = parse(alloc, rest);
+	///             alloc.insert(Expr::Add(left, right))
+	///         }
+	///         [] => panic!("nothing to parse"),
+	///     }
+	/// }
+	///
+	/// fn eval(expr: &Expr) -> i64 {
+	///     match expr {
+	///         Expr::Number(number) => *number,
+	///         Expr::Add(left, right) => eval(left) + eval(right),
+	///     }
+	/// }
+	///
+	/// let mut arena = Arena::new(1024);
+	/// let sum = arena.scope(|alloc| eval(&parse(alloc, &[1, 2, 3])));
+	/// assert_eq!(sum, 6);
+	/// // The arena can be reused right away.
+	/// let sum = arena.scope(|alloc| eval(&parse(alloc, &[4, 5])));
+	/// assert_eq!(sum, 9);
+	/// ```
+	pub fn scope<R>(&mut self, f: impl for<'a> FnOnce(&mut ArenaAlloc<'a>) -> R) -> R {
+		f(&mut self.begin_alloc())
+	}
+
+	/// Like [Arena::begin_alloc], but returns an allocator that can allocate through a shared
+	/// reference, which is what the [Allocator](core::alloc::Allocator) trait needs.
+	#[cfg(feature = "allocator_api")]
+	pub fn begin_shared<'a>(&'a mut self) -> SharedArenaAlloc<'a> {
+		SharedArenaAlloc::new(self.begin_alloc())
 	}
 }
 
+// SAFETY: The arena exclusively owns its buffer, and doesn't share it with anything that isn't
+// tied to a borrow of the arena, so it's fine to move it to another thread. Split arenas share
+// the block they were split from, but not the parts of it they use.
+unsafe impl Send for Arena {}
+
 impl Drop for Arena {
 	fn drop(&mut self) {
-		// SAFETY: We never change the length from the new method, hence we know it's not zero
-		// and that the layout is the exact same as the one we allocated with.
+		// Split arenas leave the deallocation to the block they share.
+		if self.block.is_some() {
+			return;
+		}
+
+		// SAFETY: We never change the length or the alignment from the new method, hence we know
+		// it's not zero and that the layout is the exact same as the one we allocated with.
 		unsafe {
-			dealloc(self.buffer, Layout::from_size_align(self.length, 1).unwrap());
+			dealloc(self.buffer, Layout::from_size_align(self.length, self.align).unwrap());
+		}
+	}
+}
+
+/// An allocation that is shared by the arenas [Arena::split_at] creates. It's deallocated once
+/// all of them have been dropped.
+struct SharedBlock {
+	// INVARIANT: buffer was allocated with the global allocator with layout.
+	buffer: *mut u8,
+	layout: Layout,
+}
+
+// SAFETY: The block only deallocates the buffer, which can happen on any thread.
+unsafe impl Send for SharedBlock {}
+unsafe impl Sync for SharedBlock {}
+
+impl Drop for SharedBlock {
+	fn drop(&mut self) {
+		// SAFETY: See the invariant. This is the last reference to the block, so no arena uses
+		// the buffer anymore.
+		unsafe { dealloc(self.buffer, self.layout) }
+	}
+}
+
+impl fmt::Debug for Arena {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Arena")
+			.field("buffer", &self.buffer)
+			.field("length", &self.length)
+			.field("align", &self.align)
+			.finish()
+	}
+}
+
+/// The reasons creating an [Arena] can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArenaError {
+	/// The requested length was zero.
+	ZeroLength,
+	/// The buffer could not be allocated.
+	AllocFailed,
+}
+
+impl fmt::Display for ArenaError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ArenaError::ZeroLength => write!(f, "length cannot be zero"),
+			ArenaError::AllocFailed => write!(f, "allocation failed"),
+		}
+	}
+}
+
+impl core::error::Error for ArenaError {}
+
+/// The reasons allocating from an [ArenaAlloc] can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocError {
+	/// There wasn't enough space left in the [Arena]. ``requested`` is the size in bytes of the
+	/// allocation, and ``available`` is how many bytes were left before alignment.
+	OutOfSpace {
+		/// The size in bytes of the allocation.
+		requested: usize,
+		/// The bytes that were left in the [Arena].
+		available: usize,
+	},
+	/// The size of the allocation would overflow.
+	LayoutOverflow,
+}
+
+impl fmt::Display for AllocError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			AllocError::OutOfSpace { requested, available } => write!(
+				f, "arena ran out of space, requested {} bytes but only {} were available",
+				requested, available,
+			),
+			AllocError::LayoutOverflow => write!(f, "allocation size overflowed"),
 		}
 	}
 }
 
+impl core::error::Error for AllocError {}
+
 /// Allocates items into an [Arena].
+///
+/// The allocator is deliberately not [Send]. Allocate on the thread that began the batch, and
+/// send the [ArenaBox]es instead, which are [Send] if what they contain is.
 pub struct ArenaAlloc<'a> {
 	// INVARIANTS:
 	// * The head must live for as long as 'a.
-	// * The head must be allocated until ``last``
+	// * The head must be allocated until ``last``, which points one past the end of the buffer.
+	// * ``start`` is the start of the region this allocator hands out, and is never larger than
+	//   head. It's only the start of the buffer for [Arena::begin_alloc]; [Arena::begin_alloc_at]
+	//   and [Frame]s start further in. Everything measured from it, like ``used``, ``contains``
+	//   and checkpoints, is about that region.
+	// * ``peak`` is the most bytes that have been used before the head was last moved backwards.
+	//   Space an insert reserves and gives back before returning doesn't count as used.
+	// * ``base`` is the start of the buffer the region is part of, which offsets are measured
+	//   from. It's never larger than start.
+	base: *mut u8,
+	start: *mut u8,
 	head: *mut u8,
 	last: *const u8,
+	allocations: usize,
+	peak: usize,
+	// The peak of the arena the batch came from, which is updated whenever the head moves
+	// forward. There's no Drop impl to do it once at the end, since that would keep the arena
+	// borrowed until the allocator goes out of scope.
+	arena_peak: Option<&'a mut usize>,
 	_phantom: PhantomData<&'a ()>,
 }
 
 impl<'a> ArenaAlloc<'a> {
+	/// Creates an allocator that allocates from ``offset`` bytes into a buffer.
+	///
+	/// # Safety
+	/// * ``buffer`` has to be an allocation of ``length`` bytes.
+	/// * ``offset`` can't be larger than ``length``.
+	/// * Nothing else may use the buffer from ``offset`` onwards for 'a.
+	unsafe fn from_buffer(buffer: *mut u8, length: usize, offset: usize) -> Self {
+		ArenaAlloc {
+			base: buffer,
+			start: buffer.add(offset),
+			head: buffer.add(offset),
+			// SAFETY: buffer is an allocation of length bytes, and a pointer one past the end of an
+			// allocation is allowed.
+			last: buffer.add(length),
+			allocations: 0,
+			peak: 0,
+			arena_peak: None,
+			_phantom: PhantomData,
+		}
+	}
+
 	/// Tries to allocate a space for T and insert the value into it. If there isn't enough space
 	/// for T, it will return None.
 	#[inline]
+	#[must_use = "the value is dropped right away if the box isn't used"]
 	pub fn try_insert<T>(&mut self, value: T) -> Option<ArenaBox<'a, T>> {
 		self.try_insert_with(|| value)
 	}
 
 	/// Tries to allocate a space for T and insert the value into it.
 	///
+	/// Ignoring the returned box drops the value right away, which is probably a mistake, so it
+	/// warns:
+	/// ```compile_fail
+	/// #![deny(unused_must_use)]
+	/// let mut arena = arena::Arena::new(64);
+	/// let mut alloc = arena.begin_alloc();
+	/// alloc.insert(String::from("dropped"));
+	/// ```
+	///
 	/// # Panics
 	/// * If there isn't enough space in the [Arena].
 	#[inline]
+	#[must_use = "the value is dropped right away if the box isn't used"]
 	pub fn insert<T>(&mut self, value: T) -> ArenaBox<'a, T> {
 		self.insert_with(|| value)
 	}
 
+	/// Tries to allocate a space for T that is aligned to at least ``align``, and insert the
+	/// value into it. If there isn't enough space for T, it will return None.
+	///
+	/// This is useful for aligning a buffer to a page or a cache line, which may be more than
+	/// the alignment of its type.
+	///
+	/// # Panics
+	/// * If ``align`` is not a power of two.
+	pub fn try_insert_aligned<T>(&mut self, value: T, align: usize) -> Option<ArenaBox<'a, T>> {
+		assert!(align.is_power_of_two(), "align has to be a power of two");
+
+		let align = align.max(mem::align_of::<T>());
+		let layout = Layout::from_size_align(mem::size_of::<T>(), align).ok()?;
+		let ptr = self.try_alloc_layout(layout)? as *mut T;
+		// SAFETY: The pointer was just allocated, and is aligned for T since the alignment is at
+		// least that of T.
+		unsafe {
+			ptr.write(value);
+			Some(ArenaBox::from_raw(ptr))
+		}
+	}
+
+	/// Allocates a space for T that is aligned to at least ``align``, and inserts the value into
+	/// it.
+	///
+	/// # Panics
+	/// * If ``align`` is not a power of two.
+	/// * If there isn't enough space in the [Arena].
+	pub fn insert_aligned<T>(&mut self, value: T, align: usize) -> ArenaBox<'a, T> {
+		self.try_insert_aligned(value, align).expect("Arena ran out of space")
+	}
+
 	/// Tries to allocate a space for T and insert the value the function returnsinto it.
 	/// If there isn't enough space for T, it will return None.
 	#[inline]
 	pub fn try_insert_with<F, T>(&mut self, value: F) -> Option<ArenaBox<'a, T>>
 		where F: FnOnce() -> T
 	{
-		match self.try_alloc::<T>() {
-			Some(ptr) => {
-				unsafe {
-					// SAFETY: We know that the pointer is valid because we just successfully
-					// allocated it.
-					ptr.write(value()); 
-					// SAFETY: We know that the raw pointer is not going to be accessed by anything
-					// else, because we don't access it and the lifetimes ensure that the Arena
-					// won't access it either.
-					Some(ArenaBox::from_raw(ptr))
-				}
-			}
-			None => None,
+		self.insert_with_checked(value).ok()
+	}
+
+	/// Tries to allocate a space for T and insert the value into it. Returns an [AllocError]
+	/// describing why if it fails.
+	#[inline]
+	pub fn insert_checked<T>(&mut self, value: T) -> Result<ArenaBox<'a, T>, AllocError> {
+		self.insert_with_checked(|| value)
+	}
+
+	/// Tries to allocate a space for T and insert the value the function returns into it.
+	/// Returns an [AllocError] describing why if it fails.
+	#[inline]
+	pub fn insert_with_checked<F, T>(&mut self, value: F) -> Result<ArenaBox<'a, T>, AllocError>
+		where F: FnOnce() -> T
+	{
+		let head = self.head;
+		let ptr = self.alloc_checked::<T>()?;
+		// If value panics, the allocation is given back.
+		let rollback = Rollback { alloc: self, head };
+		let value = value();
+		mem::forget(rollback);
+
+		unsafe {
+			// SAFETY: We know that the pointer is valid because we just successfully
+			// allocated it.
+			ptr.write(value);
+			// SAFETY: We know that the raw pointer is not going to be accessed by anything
+			// else, because we don't access it and the lifetimes ensure that the Arena
+			// won't access it either.
+			Ok(ArenaBox::from_raw(ptr))
 		}
 	}
 
@@ -143,23 +851,88 @@ impl<'a> ArenaAlloc<'a> {
 		self.try_insert_with(value).expect("Arena ran out of space")
 	}
 
+	/// Tries to allocate a space for T and insert the value the function returns into it. The
+	/// function gets the byte offset the value will have from the start of the buffer, which is
+	/// the same offset [ArenaAlloc::offset_of] returns for the box later. If there isn't enough
+	/// space for T, it will return None.
+	///
+	/// The offset is from the start of the whole buffer, even for an allocator from
+	/// [Arena::begin_alloc_at] or a [Frame], so adding it to [Arena::as_mut_ptr] gives back the
+	/// pointer to the value.
+	///
+	/// This is useful for values that refer to each other by offset, like the nodes of a graph.
+	/// Zero sized values don't take up space, so they get the offset of the head instead.
+	pub fn try_insert_indexed<T, F>(&mut self, value: F) -> Option<(usize, ArenaBox<'a, T>)>
+		where F: FnOnce(usize) -> T
+	{
+		let head = self.head;
+		let ptr = self.try_alloc::<T>()?;
+		let offset = match mem::size_of::<T>() {
+			0 => self.head as usize - self.base as usize,
+			_ => ptr as usize - self.base as usize,
+		};
+		// If value panics, the allocation is given back.
+		let rollback = Rollback { alloc: self, head };
+		let value = value(offset);
+		mem::forget(rollback);
+
+		// SAFETY: The pointer was just allocated, and nothing else can access it.
+		unsafe {
+			ptr.write(value);
+			Some((offset, ArenaBox::from_raw(ptr)))
+		}
+	}
+
+	/// Allocates a space for T and inserts the value the function returns into it. See
+	/// [ArenaAlloc::try_insert_indexed].
+	///
+	/// # Panics
+	/// * If there isn't enough space for T.
+	pub fn insert_indexed<T, F>(&mut self, value: F) -> (usize, ArenaBox<'a, T>)
+		where F: FnOnce(usize) -> T
+	{
+		self.try_insert_indexed(value).expect("Arena ran out of space")
+	}
+
+	/// Returns the byte offset of a box from the start of the buffer, like
+	/// [ArenaAlloc::try_insert_indexed] gives.
+	///
+	/// The box can be from anywhere in the buffer, including the bytes before where an allocator
+	/// from [Arena::begin_alloc_at] or a [Frame] starts.
+	///
+	/// # Panics
+	/// * If the box doesn't point into the buffer, or is of a zero sized type.
+	pub fn offset_of<T: ?Sized>(&self, b: &ArenaBox<T>) -> usize {
+		let ptr = b.as_ptr() as *const u8 as usize;
+		assert!(
+			mem::size_of_val::<T>(b) != 0
+				&& (self.base as usize..self.last as usize).contains(&ptr),
+			"box is not from this buffer"
+		);
+		ptr - self.base as usize
+	}
+
 	/// Allocates the space for and inserts a slice. Returns None if there is not enough space.
 	pub fn try_insert_slice<T: Copy>(&mut self, slice: &[T]) -> Option<ArenaBox<'a, [T]>> {
-		// Because the slice has been constructed before passing it here, the layout should be
-		// valid.
-		let buffer = self.try_alloc_layout(Layout::array::<T>(slice.len()).unwrap())? as *mut T;
+		self.insert_slice_checked(slice).ok()
+	}
+
+	/// Allocates the space for and inserts a slice. Returns an [AllocError] describing why if it
+	/// fails.
+	pub fn insert_slice_checked<T>(&mut self, slice: &[T]) -> Result<ArenaBox<'a, [T]>, AllocError>
+		where T: Copy
+	{
+		let buffer = self.alloc_array_checked::<T>(slice.len())?;
 
 		// SAFETY: We know that buffer is valid, and that it doesn't overlap with slice, because
 		// there should be no other pointer/reference to it.
 		unsafe {
-			std::ptr::copy(slice.as_ptr(), buffer, slice.len());
+			core::ptr::copy_nonoverlapping(slice.as_ptr(), buffer, slice.len());
 		}
 
-		let slice = unsafe {
-			std::slice::from_raw_parts_mut(buffer, slice.len()) as *mut [T]
-		};
+		let slice = core::ptr::slice_from_raw_parts_mut(buffer, slice.len());
 
-		Some(unsafe {
+		Ok(unsafe {
 			ArenaBox::from_raw(slice)
 		})
 	}
@@ -172,65 +945,358 @@ impl<'a> ArenaAlloc<'a> {
 		self.try_insert_slice(slice).expect("Arena ran out of space")
 	}
 
-	/// Tries to insert and allocate space for all the items in the iterator.
-	///
-	/// This is similar to collecting an iterator into a vector, except it utilises the fact that
-	/// this is an arena allocator to collect into a slice instead.
-	///
-	/// If the elements do not fit, it returns None.
-	pub fn try_insert_all<T>(&mut self, mut items: impl Iterator<Item = T>) -> Option<ArenaBox<'a, [T]>> {
-		let ptr = match items.next() {
-			Some(item) => self.try_insert(item)?.into_raw(),
-			None => return Some(ArenaBox::empty_slice()),
-		};
-		let mut n_elements = 1;
-
-		for item in items {
-			match self.try_insert(item) {
-				Some(item) => std::mem::forget(item),
-				None => {
-					// Drop elements that have already been added, to not leak memory.
-					// The item that we tried to add with try_insert has  already been dropped.
-					for i in 0..n_elements {
-						unsafe { ptr.add(i).drop_in_place(); }
-					}
-					
-					return None;
-				}
-			}
-			n_elements += 1;
-		}
-
-		// This is safe because slices and this arena allocator have the same memory layout
-		// if you always insert the same type.
+	/// Allocates the space for and copies a byte buffer, for example one received from the
+	/// network. Returns None if there is not enough space.
+	pub fn try_insert_bytes(&mut self, bytes: &[u8]) -> Option<ArenaBox<'a, [u8]>> {
+		let buffer = self.try_alloc_layout(Layout::array::<u8>(bytes.len()).ok()?)?;
+		// SAFETY: The buffer was just allocated for bytes.len() bytes, so it can't overlap with
+		// bytes.
 		unsafe {
-			Some(ArenaBox::from_raw(std::slice::from_raw_parts_mut(ptr, n_elements)))
+			core::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer, bytes.len());
+			Some(ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(buffer, bytes.len())))
 		}
 	}
 
-	/// Tries to insert and allocate space for all the items in the iterator.
-	///
-	/// This is similar to collecting an iterator into a vector, except it utilises the fact that
-	/// this is an arena allocator to collect into a slice instead.
+	/// Allocates the space for and copies a byte buffer, for example one received from the
+	/// network.
 	///
 	/// # Panics
-	/// * If the elements do not fit.
-	pub fn insert_all<T>(&mut self, items: impl Iterator<Item = T>) -> ArenaBox<'a, [T]> {
-		self.try_insert_all(items).expect("Arena ran out of space")
+	/// * If there isn't enough space in the [Arena].
+	pub fn insert_bytes(&mut self, bytes: &[u8]) -> ArenaBox<'a, [u8]> {
+		self.try_insert_bytes(bytes).expect("Arena ran out of space")
 	}
 
-	/// Tries to allocate a raw pointer to a T. If there isn't enough space it will return
-	/// None.
-	///
-	/// # Guarantees
-	/// * The raw pointer is aligned
-	/// * The raw pointer contains an allocation for ``T``
-	/// * The raw pointer will not be read or mutated except through the return pointer for ``'a``
-	pub fn try_alloc<T>(&mut self) -> Option<*mut T> {
-		self.try_alloc_layout(Layout::new::<T>()).map(|v| v as *mut T)
+	/// Tries to allocate a space for T and insert the default value into it. If there isn't
+	/// enough space for T, it will return None.
+	#[inline]
+	pub fn try_insert_default<T: Default>(&mut self) -> Option<ArenaBox<'a, T>> {
+		self.try_insert_with(T::default)
 	}
 
-	/// Allocates a raw pointer to a T.
+	/// Allocates a space for T and inserts the default value into it.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	#[inline]
+	pub fn insert_default<T: Default>(&mut self) -> ArenaBox<'a, T> {
+		self.insert_with(T::default)
+	}
+
+	/// Tries to allocate a slice of ``len`` elements, and fill it with default values. If there
+	/// isn't enough space, it will return None.
+	pub fn try_insert_default_slice<T>(&mut self, len: usize) -> Option<ArenaBox<'a, [T]>>
+		where T: Default
+	{
+		let buffer = self.try_alloc_array::<T>(len)?;
+
+		// If a default panics, this drops the elements that were already created.
+		let mut initialized = InitializedPrefix { ptr: buffer, len: 0 };
+		while initialized.len < len {
+			// SAFETY: The buffer has space for len elements.
+			unsafe { buffer.add(initialized.len).write(T::default()); }
+			initialized.len += 1;
+		}
+
+		Some(unsafe { ArenaBox::from_raw(initialized.finish()) })
+	}
+
+	/// Allocates a slice of ``len`` elements, and fills it with default values.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	pub fn insert_default_slice<T: Default>(&mut self, len: usize) -> ArenaBox<'a, [T]> {
+		self.try_insert_default_slice(len).expect("Arena ran out of space")
+	}
+
+	/// Tries to allocate space for and insert an array. If there isn't enough space, it will
+	/// return None.
+	///
+	/// The result can be turned into a boxed slice with [ArenaBox::into_slice].
+	#[inline]
+	pub fn try_insert_array<T, const N: usize>(
+		&mut self,
+		array: [T; N],
+	) -> Option<ArenaBox<'a, [T; N]>> {
+		self.try_insert(array)
+	}
+
+	/// Allocates space for and inserts an array.
+	///
+	/// The result can be turned into a boxed slice with [ArenaBox::into_slice].
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	#[inline]
+	pub fn insert_array<T, const N: usize>(&mut self, array: [T; N]) -> ArenaBox<'a, [T; N]> {
+		self.insert(array)
+	}
+
+	/// Allocates the space for and inserts a string. Returns None if there is not enough space.
+	pub fn try_insert_str(&mut self, string: &str) -> Option<ArenaBox<'a, str>> {
+		self.insert_str_checked(string).ok()
+	}
+
+	/// Allocates the space for and inserts a string. Returns an [AllocError] describing why if it
+	/// fails.
+	pub fn insert_str_checked(&mut self, string: &str) -> Result<ArenaBox<'a, str>, AllocError> {
+		let bytes = self.insert_slice_checked(string.as_bytes())?.leak();
+		// SAFETY: The bytes were copied from a str, so they are valid utf-8. Leaking a box of bytes
+		// doesn't leak anything, so the box can be reconstructed from it.
+		unsafe { Ok(ArenaBox::from_raw(core::str::from_utf8_unchecked_mut(bytes))) }
+	}
+
+	/// Allocates the space for and inserts a string.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	pub fn insert_str(&mut self, string: &str) -> ArenaBox<'a, str> {
+		self.try_insert_str(string).expect("Arena ran out of space")
+	}
+
+	/// Returns a writer that formats a string directly into the arena, see [ArenaStringWriter].
+	pub fn string_writer(&mut self) -> ArenaStringWriter<'a, '_> {
+		ArenaStringWriter::new(self)
+	}
+
+	/// Tries to allocate an empty [ArenaVec] with space for ``capacity`` elements. If there isn't
+	/// enough space, it will return None.
+	pub fn try_vec_with_capacity<T>(&mut self, capacity: usize) -> Option<ArenaVec<'a, T>> {
+		let ptr = self.try_alloc_array::<MaybeUninit<T>>(capacity)?;
+		let slice = core::ptr::slice_from_raw_parts_mut(ptr, capacity);
+		// SAFETY: The pointer is valid for capacity elements, and a MaybeUninit doesn't have to
+		// be initialized.
+		let buffer = unsafe { ArenaBox::from_raw(slice) };
+		Some(ArenaVec::new(buffer))
+	}
+
+	/// Allocates an empty [ArenaVec] with space for ``capacity`` elements.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	pub fn vec_with_capacity<T>(&mut self, capacity: usize) -> ArenaVec<'a, T> {
+		self.try_vec_with_capacity(capacity).expect("Arena ran out of space")
+	}
+
+	/// Tries to allocate an empty [ArenaString] with space for ``capacity`` bytes. If there isn't
+	/// enough space, it will return None.
+	pub fn try_string_with_capacity(&mut self, capacity: usize) -> Option<ArenaString<'a>> {
+		let ptr = self.try_alloc_array::<MaybeUninit<u8>>(capacity)?;
+		let slice = core::ptr::slice_from_raw_parts_mut(ptr, capacity);
+		// SAFETY: The pointer is valid for capacity bytes, and a MaybeUninit doesn't have to be
+		// initialized.
+		let buffer = unsafe { ArenaBox::from_raw(slice) };
+		Some(ArenaString::new(buffer))
+	}
+
+	/// Allocates an empty [ArenaString] with space for ``capacity`` bytes.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	pub fn string_with_capacity(&mut self, capacity: usize) -> ArenaString<'a> {
+		self.try_string_with_capacity(capacity).expect("Arena ran out of space")
+	}
+
+	/// Allocates the space for and inserts clones of all the elements in a slice. Returns None if
+	/// there is not enough space.
+	///
+	/// If the slice is [Copy], [ArenaAlloc::try_insert_slice] is faster.
+	pub fn try_insert_slice_clone<T: Clone>(&mut self, slice: &[T]) -> Option<ArenaBox<'a, [T]>> {
+		let buffer = self.try_alloc_array::<T>(slice.len())?;
+
+		// If a clone panics, this drops the elements that were already cloned.
+		let mut initialized = InitializedPrefix { ptr: buffer, len: 0 };
+		for item in slice {
+			// SAFETY: The buffer has space for slice.len() elements, and we never write more than
+			// that.
+			unsafe { buffer.add(initialized.len).write(item.clone()); }
+			initialized.len += 1;
+		}
+
+		Some(unsafe { ArenaBox::from_raw(initialized.finish()) })
+	}
+
+	/// Allocates the space for and inserts clones of all the elements in a slice.
+	///
+	/// If the slice is [Copy], [ArenaAlloc::insert_slice] is faster.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	pub fn insert_slice_clone<T: Clone>(&mut self, slice: &[T]) -> ArenaBox<'a, [T]> {
+		self.try_insert_slice_clone(slice).expect("Arena ran out of space")
+	}
+
+	/// Tries to insert and allocate space for all the items in the iterator.
+	///
+	/// This is similar to collecting an iterator into a vector, except it utilises the fact that
+	/// this is an arena allocator to collect into a slice instead.
+	///
+	/// If the elements do not fit, it returns None.
+	pub fn try_insert_all<T, I>(&mut self, items: I) -> Option<ArenaBox<'a, [T]>>
+		where I: IntoIterator<Item = T>
+	{
+		let mut items = items.into_iter();
+		// The reserved chunks together make up a single allocation, so they're counted once.
+		let allocations = self.allocations;
+		// Reserve space for as many elements as the iterator promises in one go, so we don't have
+		// to do the alignment and bounds checks for each of them.
+		let (reserved, _) = items.size_hint();
+		let ptr = self.try_alloc_array::<T>(reserved)?;
+
+		// Drops elements that have already been added if we run out of space, to not leak memory.
+		let mut initialized = InitializedPrefix { ptr, len: 0 };
+		while initialized.len < reserved {
+			match items.next() {
+				// SAFETY: We reserved space for this element.
+				Some(item) => unsafe { ptr.add(initialized.len).write(item) },
+				None => break,
+			}
+			initialized.len += 1;
+		}
+
+		// If the size hint was too small, we reserve space for the rest of the elements in chunks,
+		// so the bounds are only checked once per chunk. This works because slices and this arena
+		// allocator have the same memory layout if you always insert the same type, so the chunks
+		// end up directly after the reserved elements.
+		let mut capacity = reserved;
+		if initialized.len == reserved {
+			for item in items {
+				if initialized.len == capacity {
+					// The item is dropped if it doesn't fit.
+					let fits = self.remaining() / mem::size_of::<T>().max(1);
+					let chunk = fits.clamp(1, INSERT_ALL_CHUNK);
+					let (chunk, slot) = match self.try_alloc_array::<T>(chunk) {
+						Some(slot) => (chunk, slot),
+						// The estimate doesn't include padding, so it may be slightly too large.
+						None => match self.try_alloc::<T>() {
+							Some(slot) => (1, slot),
+							None => {
+								self.allocations = allocations;
+								return None;
+							}
+						},
+					};
+					if capacity == 0 {
+						initialized.ptr = slot;
+					}
+					debug_assert_eq!(slot, initialized.ptr.wrapping_add(capacity));
+					capacity += chunk;
+				}
+
+				// SAFETY: We reserved space for this element.
+				unsafe { initialized.ptr.add(initialized.len).write(item); }
+				initialized.len += 1;
+			}
+		}
+
+		// Give back the space that was reserved but not used. Nothing was allocated after it, and
+		// it was never used, so it isn't counted in the peak.
+		if capacity > initialized.len && mem::size_of::<T>() != 0 {
+			self.head = initialized.ptr.wrapping_add(initialized.len) as *mut u8;
+		}
+		self.allocations = allocations + 1;
+
+		// SAFETY: All the elements are initialized, and nothing else can access them.
+		unsafe {
+			Some(ArenaBox::from_raw(initialized.finish()))
+		}
+	}
+
+	/// Tries to insert and allocate space for all the items in the iterator.
+	///
+	/// This is similar to collecting an iterator into a vector, except it utilises the fact that
+	/// this is an arena allocator to collect into a slice instead.
+	///
+	/// # Panics
+	/// * If the elements do not fit.
+	pub fn insert_all<T>(&mut self, items: impl IntoIterator<Item = T>) -> ArenaBox<'a, [T]> {
+		self.try_insert_all(items).expect("Arena ran out of space")
+	}
+
+	/// Tries to collect an iterator into a boxed slice. This is the same as
+	/// [ArenaAlloc::try_insert_all], named after [Iterator::collect].
+	///
+	/// If the elements do not fit, it returns None.
+	pub fn try_collect<T, I>(&mut self, items: I) -> Option<ArenaBox<'a, [T]>>
+		where I: IntoIterator<Item = T>
+	{
+		self.try_insert_all(items)
+	}
+
+	/// Collects an iterator into a boxed slice. This is the same as [ArenaAlloc::insert_all],
+	/// named after [Iterator::collect].
+	///
+	/// # Panics
+	/// * If the elements do not fit.
+	pub fn collect_slice<T>(&mut self, items: impl IntoIterator<Item = T>) -> ArenaBox<'a, [T]> {
+		self.insert_all(items)
+	}
+
+	/// Tries to insert and allocate space for all the items in an iterator that knows its exact
+	/// length. This reserves the space for all of them at once, so it's the fastest way to insert
+	/// an iterator.
+	///
+	/// If the iterator yields fewer items than it said it would, the slice only contains the ones
+	/// it yielded, and if it yields more, the extra ones are not taken from it.
+	///
+	/// If the elements do not fit, it returns None.
+	pub fn try_insert_iter_exact<T, I>(&mut self, mut items: I) -> Option<ArenaBox<'a, [T]>>
+		where I: ExactSizeIterator<Item = T>
+	{
+		let len = items.len();
+		let ptr = self.try_alloc_array::<T>(len)?;
+
+		// Drops the elements that have already been added if the iterator panics.
+		let mut initialized = InitializedPrefix { ptr, len: 0 };
+		while initialized.len < len {
+			match items.next() {
+				// SAFETY: We reserved space for len elements.
+				Some(item) => unsafe { ptr.add(initialized.len).write(item) },
+				None => break,
+			}
+			initialized.len += 1;
+		}
+
+		// Give back the space that wasn't used, if the iterator was shorter than it said. Like in
+		// try_insert_all, it isn't counted in the peak.
+		if initialized.len < len && mem::size_of::<T>() != 0 {
+			self.head = ptr.wrapping_add(initialized.len) as *mut u8;
+		}
+
+		// SAFETY: All the elements are initialized, and nothing else can access them.
+		unsafe { Some(ArenaBox::from_raw(initialized.finish())) }
+	}
+
+	/// Inserts and allocates space for all the items in an iterator that knows its exact length.
+	/// See [ArenaAlloc::try_insert_iter_exact].
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena].
+	pub fn insert_iter_exact<T, I>(&mut self, items: I) -> ArenaBox<'a, [T]>
+		where I: ExactSizeIterator<Item = T>
+	{
+		self.try_insert_iter_exact(items).expect("Arena ran out of space")
+	}
+
+	/// Tries to allocate a raw pointer to a T. If there isn't enough space it will return
+	/// None.
+	///
+	/// # Guarantees
+	/// * The raw pointer is aligned
+	/// * The raw pointer contains an allocation for ``T``
+	/// * The raw pointer will not be read or mutated except through the return pointer for ``'a``
+	pub fn try_alloc<T>(&mut self) -> Option<*mut T> {
+		self.try_alloc_layout(Layout::new::<T>()).map(|v| v as *mut T)
+	}
+
+	/// Tries to allocate a raw pointer to a T. Returns an [AllocError] describing why if it
+	/// fails.
+	///
+	/// This gives the same guarantees as [ArenaAlloc::try_alloc].
+	pub fn alloc_checked<T>(&mut self) -> Result<*mut T, AllocError> {
+		self.alloc_layout_checked(Layout::new::<T>()).map(|v| v as *mut T)
+	}
+
+	/// Allocates a raw pointer to a T.
 	///
 	/// # Guarantees
 	/// * The raw pointer is aligned
@@ -243,147 +1309,2339 @@ impl<'a> ArenaAlloc<'a> {
 		self.try_alloc::<T>().expect("Arena ran out of space")
 	}
 
-	#[inline]
-	fn try_alloc_layout(&mut self, layout: Layout) -> Option<*mut u8> {
-		if layout.size() == 0 { return Some(NonNull::dangling().as_ptr()); }
+	/// Tries to allocate memory for the given layout. If there isn't enough space it will return
+	/// None.
+	///
+	/// This is useful when the layout is only known at runtime, for example when it comes from
+	/// across an FFI boundary.
+	///
+	/// # Guarantees
+	/// * The raw pointer is aligned to ``layout.align()``
+	/// * The raw pointer contains an allocation of ``layout.size()`` bytes
+	/// * The raw pointer will not be read or mutated except through the return pointer for ``'a``
+	#[inline]
+	pub fn try_alloc_layout(&mut self, layout: Layout) -> Option<*mut u8> {
+		// Zero sized allocations don't need any memory, but the pointer still has to be aligned.
+		if layout.size() == 0 {
+			self.allocations += 1;
+			return Some(layout.align() as *mut u8);
+		}
+
+		// TODO: We may want to be less pedantic here for performance reasons.
+		// (layout.align() - 1) is fine because align is guaranteed to not be zero.
+		let head = self.head as usize;
+		let start = head.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+		let end = start.checked_add(layout.size())?;
+		if end > self.last as usize {
+			return None;
+		}
+
+		// SAFETY: We know that start and end do not go past the end of the buffer, and that
+		// neither of them overflowed.
+		unsafe {
+			let value = self.head.add(start - head);
+			self.head = value.add(layout.size());
+			self.update_arena_peak();
+			self.allocations += 1;
+			Some(value)
+		}
+	}
+
+	/// Allocates memory for the given layout.
+	///
+	/// This gives the same guarantees as [ArenaAlloc::try_alloc_layout].
+	///
+	/// # Panics
+	/// * If there is not enough space for the layout in the Arena.
+	pub fn alloc_layout(&mut self, layout: Layout) -> *mut u8 {
+		self.try_alloc_layout(layout).expect("Arena ran out of space")
+	}
+
+	/// Moves the head forward to the next multiple of ``align``, without allocating anything, so
+	/// the next allocation starts aligned. If there aren't enough bytes left to reach it, the head
+	/// is moved to the end of the buffer instead.
+	///
+	/// This is useful when mixing raw bytes with aligned fields, for example when building a
+	/// serialized structure in place.
+	///
+	/// # Panics
+	/// * If ``align`` is not a power of two.
+	///
+	/// ```
+	/// let mut arena = arena::Arena::new(64);
+	/// let mut alloc = arena.begin_alloc();
+	/// let _ = alloc.insert_bytes(b"abc");
+	/// alloc.align_to(16);
+	/// let bytes = alloc.insert_bytes(b"d");
+	/// assert_eq!(bytes.as_ptr() as *const u8 as usize % 16, 0);
+	/// ```
+	pub fn align_to(&mut self, align: usize) {
+		assert!(align.is_power_of_two(), "align has to be a power of two");
+
+		let head = self.head as usize;
+		let padding = head.wrapping_neg() & (align - 1);
+		// SAFETY: The head is never moved past the end of the buffer.
+		self.head = unsafe { self.head.add(padding.min(self.remaining())) };
+		self.update_arena_peak();
+	}
+
+	/// Records how far into the buffer the head has got, in the arena the batch came from.
+	#[inline]
+	fn update_arena_peak(&mut self) {
+		let used = self.head as usize - self.base as usize;
+		if let Some(arena_peak) = self.arena_peak.as_deref_mut() {
+			*arena_peak = (*arena_peak).max(used);
+		}
+	}
+
+	/// Tries to allocate space for a T, without initializing it. If there isn't enough space it
+	/// will return None.
+	///
+	/// This lets you initialize large values in place, instead of constructing them on the stack
+	/// and moving them into the arena.
+	pub fn try_alloc_uninit<T>(&mut self) -> Option<ArenaBox<'a, MaybeUninit<T>>> {
+		let ptr = self.try_alloc::<MaybeUninit<T>>()?;
+		// SAFETY: A MaybeUninit doesn't have to be initialized, and the pointer was just allocated.
+		unsafe { Some(ArenaBox::from_raw(ptr)) }
+	}
+
+	/// Allocates space for a T, without initializing it.
+	///
+	/// # Panics
+	/// * If there is not enough space for a T in the Arena.
+	pub fn alloc_uninit<T>(&mut self) -> ArenaBox<'a, MaybeUninit<T>> {
+		self.try_alloc_uninit().expect("Arena ran out of space")
+	}
+
+	/// Tries to allocate space for ``len`` elements of T, without initializing them. If there
+	/// isn't enough space it will return None.
+	///
+	/// Once the elements are written, [ArenaBox::assume_init] turns it into a boxed slice of T,
+	/// without copying anything.
+	pub fn try_alloc_slice_uninit<T>(
+		&mut self,
+		len: usize,
+	) -> Option<ArenaBox<'a, [MaybeUninit<T>]>> {
+		let ptr = self.try_alloc_array::<MaybeUninit<T>>(len)?;
+		// SAFETY: The pointer is valid for len elements, and a MaybeUninit doesn't have to be
+		// initialized.
+		unsafe { Some(ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len))) }
+	}
+
+	/// Allocates space for ``len`` elements of T, without initializing them.
+	///
+	/// # Panics
+	/// * If there is not enough space in the Arena.
+	///
+	/// ```
+	/// let mut arena = arena::Arena::new(64);
+	/// let mut alloc = arena.begin_alloc();
+	/// let mut slice = alloc.alloc_slice_uninit::<u32>(4);
+	/// for (i, element) in slice.iter_mut().enumerate() {
+	///     element.write(i as u32 * 10);
+	/// }
+	/// // SAFETY: Every element was written.
+	/// let slice = unsafe { slice.assume_init() };
+	/// assert_eq!(&*slice, &[0, 10, 20, 30]);
+	/// ```
+	pub fn alloc_slice_uninit<T>(&mut self, len: usize) -> ArenaBox<'a, [MaybeUninit<T>]> {
+		self.try_alloc_slice_uninit(len).expect("Arena ran out of space")
+	}
+
+	/// Tries to allocate space for a T, with all of its bytes set to zero. If there isn't enough
+	/// space it will return None.
+	///
+	/// Like [Box::new_zeroed](alloc::boxed::Box::new_zeroed), this returns a [MaybeUninit],
+	/// since not all types are valid when zeroed. If T is, it's safe to call
+	/// [ArenaBox::assume_init] on the result.
+	pub fn try_alloc_zeroed<T>(&mut self) -> Option<ArenaBox<'a, MaybeUninit<T>>> {
+		let mut value = self.try_alloc_uninit::<T>()?;
+		// SAFETY: The pointer is valid for one T.
+		unsafe { value.as_mut_ptr().write_bytes(0, 1); }
+		Some(value)
+	}
+
+	/// Allocates space for a T, with all of its bytes set to zero.
+	///
+	/// Like [Box::new_zeroed](alloc::boxed::Box::new_zeroed), this returns a [MaybeUninit],
+	/// since not all types are valid when zeroed. If T is, it's safe to call
+	/// [ArenaBox::assume_init] on the result.
+	///
+	/// # Panics
+	/// * If there is not enough space for a T in the Arena.
+	pub fn alloc_zeroed<T>(&mut self) -> ArenaBox<'a, MaybeUninit<T>> {
+		self.try_alloc_zeroed().expect("Arena ran out of space")
+	}
+
+	/// Tries to allocate space for ``len`` elements of T, with all of their bytes set to zero. If
+	/// there isn't enough space it will return None.
+	///
+	/// Like [Box::new_zeroed_slice](alloc::boxed::Box::new_zeroed_slice), this returns
+	/// [MaybeUninit]s, since not all types are valid when zeroed. If T is, it's safe to call
+	/// [ArenaBox::assume_init] on the result.
+	pub fn try_alloc_zeroed_slice<T>(
+		&mut self,
+		len: usize,
+	) -> Option<ArenaBox<'a, [MaybeUninit<T>]>> {
+		let ptr = self.try_alloc_array::<MaybeUninit<T>>(len)?;
+		// SAFETY: The pointer is valid for len elements, and a MaybeUninit doesn't have to be
+		// initialized.
+		unsafe {
+			ptr.write_bytes(0, len);
+			Some(ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len)))
+		}
+	}
+
+	/// Allocates space for ``len`` elements of T, with all of their bytes set to zero.
+	///
+	/// Like [Box::new_zeroed_slice](alloc::boxed::Box::new_zeroed_slice), this returns
+	/// [MaybeUninit]s, since not all types are valid when zeroed. If T is, it's safe to call
+	/// [ArenaBox::assume_init] on the result.
+	///
+	/// # Panics
+	/// * If there is not enough space in the Arena.
+	pub fn alloc_zeroed_slice<T>(&mut self, len: usize) -> ArenaBox<'a, [MaybeUninit<T>]> {
+		self.try_alloc_zeroed_slice(len).expect("Arena ran out of space")
+	}
+
+	/// Returns the number of bytes left in the [Arena] for this batch of allocations.
+	///
+	/// The head may not be aligned for the next allocation, so the space actually available for
+	/// a given type may be slightly less than this, because of padding.
+	pub fn remaining(&self) -> usize {
+		self.last as usize - self.head as usize
+	}
+
+	/// Returns how many more values of T fit, taking the padding needed to align the head for a
+	/// T into account. Zero sized types always fit, so for them this returns ``usize::MAX``.
+	pub fn remaining_for<T>(&self) -> usize {
+		let size = mem::size_of::<T>();
+		if size == 0 {
+			return usize::MAX;
+		}
+
+		let padding = (self.head as usize).wrapping_neg() & (mem::align_of::<T>() - 1);
+		self.remaining().saturating_sub(padding) / size
+	}
+
+	/// Returns the number of bytes that have been used by this batch of allocations, including
+	/// any padding.
+	///
+	/// ``used() + remaining()`` is always equal to ``capacity()``.
+	pub fn used(&self) -> usize {
+		self.head as usize - self.start as usize
+	}
+
+	/// Returns the total number of bytes this allocator can use.
+	pub fn capacity(&self) -> usize {
+		self.last as usize - self.start as usize
+	}
+
+	/// Returns whether the box points into the part of the buffer this allocator hands out.
+	/// For an allocator from [Arena::begin_alloc_at] or a [Frame], that doesn't include the bytes
+	/// before where it started.
+	///
+	/// Boxes of zero sized types don't point into any buffer, so they are never contained.
+	pub fn contains<T: ?Sized>(&self, b: &ArenaBox<T>) -> bool {
+		core::mem::size_of_val::<T>(b) != 0 && self.contains_ptr(b.as_ptr() as *const u8)
+	}
+
+	/// Returns whether the pointer lies within the part of the buffer this allocator hands out.
+	pub fn contains_ptr(&self, ptr: *const u8) -> bool {
+		(self.start as usize..self.last as usize).contains(&(ptr as usize))
+	}
+
+	/// Returns statistics about this batch of allocations. To see how much the arena needed
+	/// over several batches, use [Arena::peak_bytes].
+	pub fn stats(&self) -> Stats {
+		Stats {
+			allocations: self.allocations,
+			bytes_used: self.used(),
+			peak_bytes: self.peak.max(self.used()),
+		}
+	}
+
+	/// Saves the current position of the allocator, so that it can be returned to with
+	/// [ArenaAlloc::restore].
+	pub fn checkpoint(&self) -> Checkpoint {
+		Checkpoint { head: self.head }
+	}
+
+	/// Begins a temporary scope of allocations, which gives its memory back to this allocator
+	/// once it's dropped. See [Frame].
+	pub fn frame(&mut self) -> Frame<'_, 'a> {
+		Frame::new(self)
+	}
+
+	/// Rolls the allocator back to a [Checkpoint], so that the memory allocated after it can be
+	/// reused.
+	///
+	/// # Safety
+	/// * No [ArenaBox] (or other pointer) allocated after the checkpoint may be used after this.
+	///   The memory they point to will be handed out again by later allocations, so using them
+	///   would alias those allocations, and dropping them would drop whatever was written there
+	///   later. Those boxes have to be dropped or forgotten before calling this.
+	///
+	/// # Panics
+	/// * If the checkpoint is not from this batch of allocations.
+	pub unsafe fn restore(&mut self, checkpoint: Checkpoint) {
+		assert!(
+			self.start <= checkpoint.head && checkpoint.head <= self.head,
+			"checkpoint is not from this allocator"
+		);
+		self.peak = self.peak.max(self.used());
+		self.head = checkpoint.head;
+	}
+
+	/// Grows a boxed slice by ``additional`` elements in place, if it is the last thing that was
+	/// allocated. Returns false and leaves the slice alone if something was allocated after it,
+	/// if it isn't from this allocator, or if there isn't enough space.
+	///
+	/// # Safety
+	/// * The new elements at the end of the slice are uninitialized. They have to be written, for
+	///   example through [ArenaBox::as_mut_ptr], before the slice is read from or dropped.
+	pub unsafe fn grow_last<T>(&mut self, b: &mut ArenaBox<'a, [T]>, additional: usize) -> bool {
+		let ptr = b.as_mut_ptr() as *mut T;
+		let len = b.len();
+		let end = ptr.wrapping_add(len) as *mut u8;
+		// If nothing was allocated, the slice may end at the start of this buffer, but it can't
+		// be from it.
+		if mem::size_of::<T>() == 0 || end != self.head || self.head == self.start {
+			return false;
+		}
+
+		let bytes = match mem::size_of::<T>().checked_mul(additional) {
+			Some(bytes) if bytes <= self.remaining() => bytes,
+			_ => return false,
+		};
+
+		self.head = self.head.add(bytes);
+		self.update_arena_peak();
+		let grown = core::ptr::slice_from_raw_parts_mut(ptr, len + additional);
+		// SAFETY: The slice now covers the memory that was just given to it. Writing over the
+		// old box doesn't drop anything, since the new box owns the same elements.
+		core::ptr::write(b, ArenaBox::from_raw(grown));
+		true
+	}
+
+	/// Shortens a boxed slice to ``new_len`` elements, dropping the rest. If the slice is the last
+	/// thing that was allocated, the space of the dropped elements is given back to the allocator.
+	///
+	/// # Safety
+	/// * No pointers into the dropped elements may be used after this, since the memory may be
+	///   handed out again by later allocations.
+	///
+	/// # Panics
+	/// * If ``new_len`` is larger than the length of the slice.
+	pub unsafe fn shrink_last<T>(&mut self, b: &mut ArenaBox<'a, [T]>, new_len: usize) {
+		let len = b.len();
+		assert!(new_len <= len, "new_len is larger than the length of the slice");
+
+		let ptr = b.as_mut_ptr() as *mut T;
+		let end = ptr.wrapping_add(len) as *mut u8;
+		// Shorten the box before dropping the tail, so that a panic while dropping doesn't make
+		// the box drop the elements again.
+		core::ptr::write(b, ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, new_len)));
+		if mem::size_of::<T>() != 0 && end == self.head && self.head != self.start {
+			self.peak = self.peak.max(self.used());
+			self.head = ptr.add(new_len) as *mut u8;
+		}
+		let tail = core::ptr::slice_from_raw_parts_mut(ptr.add(new_len), len - new_len);
+		core::ptr::drop_in_place(tail);
+	}
+
+	/// Tries to allocate space for ``len`` elements of ``T``. Returns None if there isn't enough
+	/// space, or if the size of the array would overflow.
+	#[inline]
+	fn try_alloc_array<T>(&mut self, len: usize) -> Option<*mut T> {
+		self.try_alloc_layout(Layout::array::<T>(len).ok()?).map(|v| v as *mut T)
+	}
+
+	/// Like [ArenaAlloc::try_alloc_array], but tells you why it failed.
+	#[inline]
+	fn alloc_array_checked<T>(&mut self, len: usize) -> Result<*mut T, AllocError> {
+		let layout = Layout::array::<T>(len).map_err(|_| AllocError::LayoutOverflow)?;
+		self.alloc_layout_checked(layout).map(|v| v as *mut T)
+	}
+
+	#[inline]
+	fn alloc_layout_checked(&mut self, layout: Layout) -> Result<*mut u8, AllocError> {
+		match self.try_alloc_layout(layout) {
+			Some(ptr) => Ok(ptr),
+			None => Err(AllocError::OutOfSpace {
+				requested: layout.size(),
+				available: self.remaining(),
+			}),
+		}
+	}
+}
+
+impl fmt::Debug for ArenaAlloc<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ArenaAlloc")
+			.field("used", &self.used())
+			.field("remaining", &self.remaining())
+			.field("capacity", &self.capacity())
+			.finish()
+	}
+}
+
+/// Statistics about a single batch of allocations, see [ArenaAlloc::stats]. They start over
+/// with every batch; [Arena::peak_bytes] keeps track of the most any batch has used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+	/// The number of successful allocations, including zero sized ones. Inserting everything
+	/// from an iterator with [ArenaAlloc::insert_all] counts once, however many chunks it had to
+	/// reserve.
+	pub allocations: usize,
+	/// The number of bytes currently used, including padding.
+	pub bytes_used: usize,
+	/// The most bytes that have been used at once. This is only different from ``bytes_used`` if
+	/// the allocator has been rolled back, for example with [ArenaAlloc::restore].
+	///
+	/// Space that was only reserved while inserting isn't counted, like the space
+	/// [ArenaAlloc::insert_all] reserves ahead of the iterator and gives back when it runs out.
+	pub peak_bytes: usize,
+}
+
+/// A saved position of an [ArenaAlloc], see [ArenaAlloc::checkpoint].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+	head: *mut u8,
+}
+
+/// Moves the head of an allocator back to where it was before an allocation, unless it's
+/// forgotten. This gives the allocation back if initializing it panics.
+struct Rollback<'r, 'a> {
+	alloc: &'r mut ArenaAlloc<'a>,
+	head: *mut u8,
+}
+
+impl Drop for Rollback<'_, '_> {
+	fn drop(&mut self) {
+		self.alloc.head = self.head;
+		self.alloc.allocations -= 1;
+	}
+}
+
+/// How many elements [ArenaAlloc::try_insert_all] reserves at a time once it runs past the size
+/// hint of the iterator.
+const INSERT_ALL_CHUNK: usize = 256;
+
+/// The initialized start of a slice that is being filled in. If it's dropped before it's
+/// finished, which happens if filling in an element panics, it drops the initialized elements.
+struct InitializedPrefix<T> {
+	// INVARIANT: The first len elements of ptr are initialized.
+	ptr: *mut T,
+	len: usize,
+}
+
+impl<T> InitializedPrefix<T> {
+	/// Returns the initialized slice, and makes sure it is not dropped.
+	fn finish(self) -> *mut [T] {
+		let prefix = core::mem::ManuallyDrop::new(self);
+		core::ptr::slice_from_raw_parts_mut(prefix.ptr, prefix.len)
+	}
+}
+
+impl<T> Drop for InitializedPrefix<T> {
+	fn drop(&mut self) {
+		// SAFETY: The first len elements are initialized, and since the prefix was never finished
+		// nothing else can drop them.
+		unsafe {
+			core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(self.ptr, self.len));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::string::{String, ToString};
+	use std::{format, println, vec};
+
+	#[test]
+	fn create_arena() {
+		let _arena = Arena::new(512);
+	}
+
+	#[test]
+	fn try_create_arena() {
+		assert_eq!(Arena::try_new(0).err(), Some(ArenaError::ZeroLength));
+		assert_eq!(Arena::try_new(usize::MAX).err(), Some(ArenaError::AllocFailed));
+		assert!(Arena::try_new(512).is_ok());
+	}
+
+	#[test]
+	fn arena_from_vec() {
+		let buffer = Vec::with_capacity(64);
+		let start = buffer.as_ptr();
+		let mut arena = Arena::from_vec(buffer);
+		assert_eq!(arena.buffer as *const u8, start);
+
+		let mut alloc = arena.begin_alloc();
+		let string = alloc.insert("Hello".to_string());
+		assert_eq!(*string, "Hello");
+	}
+
+	#[test]
+	fn arena_into_vec() {
+		let buffer = Vec::with_capacity(64);
+		let start = buffer.as_ptr();
+		let mut arena = Arena::from_vec(buffer);
+		let _ = arena.begin_alloc().insert(5u32);
+
+		let buffer = arena.into_vec();
+		assert_eq!(buffer.as_ptr(), start);
+		assert_eq!(buffer.capacity(), 64);
+
+		let buffer = Arena::with_align(64, 8).into_vec();
+		assert!(buffer.capacity() >= 64);
+	}
+
+	#[test]
+	fn allocate_numbers() {
+		let mut arena = Arena::new(512);
+		let mut allocator = arena.begin_alloc();
+
+		let hello = allocator.insert(5.2);
+		let _ = allocator.insert(5);
+
+		// Without this drop, the next ``area.begin()`` will not work, because the drop call at the
+		// end of the scope will try to drop hello, but the memory might have been overwritten.
+		std::mem::drop(hello);
+
+		let mut allocator = arena.begin_alloc();
+
+		let my_string = allocator.insert("Hello, World!".to_string());
+		println!("{}", my_string);
+	}
+
+	#[test]
+	fn enum_testing() {
+		#[derive(Debug)]
+		#[allow(dead_code)]
+		enum Ast<'a> {
+			Number(i64),
+			BinaryOperator {
+				left:  ArenaBox<'a, Ast<'a>>,
+				right: ArenaBox<'a, Ast<'a>>,
+				operator: char,
+			}
+		}
+
+		fn parse_stuff<'a>(arena: &mut ArenaAlloc<'a>) -> ArenaBox<'a, Ast<'a>> {
+			let left  = arena.insert(Ast::Number(125));
+			let right = arena.insert(Ast::Number(24));
+
+			arena.insert(Ast::BinaryOperator { left, right, operator: '+' })
+		}
+
+		let mut arena = Arena::new(1024);
+		let mut arena = arena.begin_alloc();
+		let ast = parse_stuff(&mut arena);
+
+		println!("{:?}", ast);
+	}
+
+	#[test]
+	fn mass_allocate() {
+		let mut arena = Arena::new(9000);
+		let mut insert = arena.begin_alloc();
+
+		let _: Vec<_> = (0..1000u64).map(|v| insert.insert(v)).collect();
+	}
+
+	#[should_panic]
+	#[test]
+	fn over_allocate() {
+		let mut arena = Arena::new(16);
+		let mut insert = arena.begin_alloc();
+		let _ = insert.insert(5u64);
+		let _ = insert.insert(5u64);
+		let _ = insert.insert(5u64);
+	}
+	
+	#[test]
+	fn allocate_whole_buffer() {
+		// Aligned, so that there is no padding before the first u64.
+		let mut arena = Arena::with_align(16, 8);
+		let mut insert = arena.begin_alloc();
+		let _ = insert.insert(5u64);
+		let _ = insert.insert(5u64);
+		assert_eq!(insert.remaining(), 0);
+	}
+
+	#[test]
+	fn insert_all() {
+		let mut arena = Arena::new(1400);
+
+		{
+			let mut alloc = arena.begin_alloc();
+
+			let slice = alloc.insert_all(0..100u64);
+			// assert_eq!(slice.len(), 100);
+
+			for i in 0..100u64 {
+				assert_eq!(slice[i as usize], i);
+			}
+		}
+
+		{
+			let mut alloc = arena.begin_alloc();
+			let slice = alloc.insert_all((0..50).map(|v| format!("{}", v)));
+			assert_eq!(slice.len(), 50);
+
+			for i in 0..50 {
+				assert_eq!(slice[i].parse(), Ok(i));
+			}
+		}
+	}
+
+	#[test]
+	fn insert_all_fail() {
+		let mut arena = Arena::new(50);
+		let mut alloc = arena.begin_alloc();
+		let slice = alloc.try_insert_all((0..50).map(|v| format!("{}", v)));
+		assert!(slice.is_none());
+	}
+
+	#[test]
+	fn insert_slice() {
+		let mut arena = Arena::new(5600);
+		let mut alloc = arena.begin_alloc();
+		let arena_slice = alloc.insert_slice(&[1, 2, 3, 4]);
+
+		assert_eq!(arena_slice.len(), 4);
+		assert_eq!(arena_slice[0], 1);
+		assert_eq!(arena_slice[1], 2);
+		assert_eq!(arena_slice[2], 3);
+		assert_eq!(arena_slice[3], 4);
+	}
+
+	#[test]
+	fn remaining() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let before = alloc.remaining();
+
+		let _ = alloc.insert(5u8);
+		assert_eq!(alloc.remaining(), before - 1);
+	}
+
+	#[test]
+	fn used_and_capacity() {
+		let mut arena = Arena::with_align(512, 8);
+		let mut alloc = arena.begin_alloc();
+		assert_eq!(alloc.used(), 0);
+		assert_eq!(alloc.used() + alloc.remaining(), alloc.capacity());
+
+		let _ = alloc.insert(1u64);
+		assert_eq!(alloc.used(), std::mem::size_of::<u64>());
+		assert_eq!(alloc.used() + alloc.remaining(), alloc.capacity());
+	}
+
+	#[test]
+	fn aligned_arena() {
+		#[repr(align(64))]
+		struct CacheLine(u8);
+
+		let mut arena = Arena::with_align(512, 64);
+		let start = arena.buffer;
+		let mut alloc = arena.begin_alloc();
+		let line = alloc.insert(CacheLine(3));
+		assert_eq!(line.as_ptr() as *const u8, start as *const u8);
+		assert_eq!(line.0, 3);
+	}
+
+	#[test]
+	fn double_ended_iterator() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let mut iter = alloc.insert(vec![1, 2, 3].into_iter());
+
+		assert_eq!(iter.next_back(), Some(3));
+		assert_eq!(iter.next(), Some(1));
+		assert_eq!(iter.next_back(), Some(2));
+		assert_eq!(iter.next_back(), None);
+	}
+
+	#[test]
+	fn aligned_zero_sized() {
+		#[repr(align(8))]
+		struct Aligned([u8; 0]);
+
+		let mut arena = Arena::new(16);
+		let mut alloc = arena.begin_alloc();
+		let aligned = alloc.insert(Aligned([]));
+		assert_eq!(aligned.as_ptr() as usize % 8, 0);
+		assert_eq!(alloc.used(), 0);
+	}
+
+	#[test]
+	fn huge_allocation() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let _ = alloc.insert(1u8);
+
+		let huge = Layout::from_size_align(isize::MAX as usize, 1).unwrap();
+		assert!(alloc.try_alloc_layout(huge).is_none());
+		let huge = Layout::from_size_align(isize::MAX as usize - 7, 8).unwrap();
+		assert!(alloc.try_alloc_layout(huge).is_none());
+		assert_eq!(alloc.used(), 1);
+	}
+
+	#[test]
+	fn huge_array() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		assert!(alloc.try_alloc_array::<u64>(usize::MAX).is_none());
+		assert!(alloc.try_alloc_array::<u64>(usize::MAX / 8).is_none());
+		assert!(alloc.try_alloc_array::<u64>(4).is_some());
+	}
+
+	#[test]
+	fn huge_slice() {
+		// A slice can't be larger than isize::MAX bytes, so the layout of one passed to
+		// try_insert_slice never overflows. The methods that take a length can, though.
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let _a = alloc.insert(1u8);
+		let len = usize::MAX / 8 + 1;
+		assert!(alloc.try_insert_default_slice::<u64>(len).is_none());
+		assert!(alloc.try_alloc_slice_uninit::<u64>(len).is_none());
+		assert!(alloc.try_alloc_zeroed_slice::<u64>(len).is_none());
+		assert!(alloc.try_vec_with_capacity::<u64>(len).is_none());
+		assert_eq!(alloc.alloc_array_checked::<u64>(len).err(), Some(AllocError::LayoutOverflow));
+		assert_eq!(alloc.used(), 1);
+		assert_eq!(alloc.try_insert_slice(&[1u64, 2]).as_deref(), Some(&[1, 2][..]));
+	}
+
+	#[test]
+	fn into_inner() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let string = alloc.insert("Hello, World!".to_string()).into_inner();
+		assert_eq!(string, "Hello, World!");
+	}
+
+	#[test]
+	fn map() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+
+		let number = alloc.insert(5u32);
+		let ptr = number.as_ptr() as usize;
+		let number = number.map(|v| v as f32 * 2.0);
+		assert_eq!(*number, 10.0);
+		assert_eq!(number.as_ptr() as usize, ptr);
+
+		let string = alloc.insert("Hello".to_string());
+		let length = string.map(|v| v.len() as u8);
+		assert_eq!(*length, 5);
+	}
+
+	#[should_panic]
+	#[test]
+	fn map_larger() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		alloc.insert(5u8).map(|v| v as u64);
+	}
+
+	#[test]
+	fn insert_slice_clone() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let strings = ["a".to_string(), "b".to_string(), "c".to_string()];
+		let arena_strings = alloc.insert_slice_clone(&strings);
+		assert_eq!(&*arena_strings, &strings[..]);
+	}
+
+	#[test]
+	fn insert_slice_clone_panic() {
+		use std::cell::Cell;
+		use std::panic::{catch_unwind, AssertUnwindSafe};
+
+		struct Counted<'c> {
+			clones: &'c Cell<usize>,
+			drops: &'c Cell<usize>,
+		}
+
+		impl Clone for Counted<'_> {
+			fn clone(&self) -> Self {
+				assert!(self.clones.get() < 2, "clone failed");
+				self.clones.set(self.clones.get() + 1);
+				Counted { clones: self.clones, drops: self.drops }
+			}
+		}
+
+		impl Drop for Counted<'_> {
+			fn drop(&mut self) {
+				self.drops.set(self.drops.get() + 1);
+			}
+		}
+
+		let clones = Cell::new(0);
+		let drops = Cell::new(0);
+		let items = [
+			Counted { clones: &clones, drops: &drops },
+			Counted { clones: &clones, drops: &drops },
+			Counted { clones: &clones, drops: &drops },
+		];
+
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let result = catch_unwind(AssertUnwindSafe(|| {
+			alloc.insert_slice_clone(&items);
+		}));
+		assert!(result.is_err());
+		assert_eq!(clones.get(), 2);
+		assert_eq!(drops.get(), 2);
+	}
+
+	#[test]
+	fn insert_str() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let string = alloc.insert_str("héllo");
+		assert_eq!(&*string, "héllo");
+		assert_eq!(string.len(), "héllo".len());
+	}
+
+	#[test]
+	fn insert_all_size_hint() {
+		let mut arena = Arena::new(4096);
+		let mut alloc = arena.begin_alloc();
+
+		let exact = alloc.insert_all(0..100u32);
+		let unknown = alloc.insert_all((0..100u32).filter(|_| true));
+		let too_small = alloc.insert_all((0..50u32).chain((50..100).filter(|_| true)));
+		assert_eq!(exact, unknown);
+		assert_eq!(exact, too_small);
+
+		let empty = alloc.insert_all((0..0u32).filter(|_| true));
+		assert_eq!(empty.len(), 0);
+	}
+
+	#[test]
+	fn insert_all_partial_drop() {
+		use std::rc::Rc;
+
+		let item = Rc::new(());
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		assert!(alloc.try_insert_all((0..4).map(|_| item.clone())).is_some());
+		assert_eq!(Rc::strong_count(&item), 1);
+
+		let items = (0..100).map(|_| item.clone()).filter(|_| true);
+		assert!(alloc.try_insert_all(items).is_none());
+		assert_eq!(Rc::strong_count(&item), 1);
+	}
+
+	#[test]
+	fn checkpoint_restore() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let first = alloc.insert(1u32);
+
+		let checkpoint = alloc.checkpoint();
+		let used = alloc.used();
+		let second = alloc.insert(2u32);
+		let ptr = second.as_ptr();
+		drop(second);
+
+		unsafe { alloc.restore(checkpoint); }
+		assert_eq!(alloc.used(), used);
+		let third = alloc.insert(3u32);
+		assert_eq!(third.as_ptr(), ptr);
+		assert_eq!(*first, 1);
+		assert_eq!(*third, 3);
+	}
+
+	#[test]
+	fn alloc_uninit() {
+		let mut arena = Arena::new(8192);
+		let mut alloc = arena.begin_alloc();
+		let mut buffer = alloc.alloc_uninit::<[u8; 4096]>();
+
+		let ptr = buffer.as_mut_ptr() as *mut u8;
+		for i in 0..4096 {
+			unsafe { ptr.add(i).write(i as u8); }
+		}
+
+		let buffer = unsafe { buffer.assume_init() };
+		assert!(buffer.iter().enumerate().all(|(i, &v)| v == i as u8));
+	}
+
+	#[test]
+	fn alloc_zeroed() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let _ = alloc.insert(1u8);
+
+		let value = unsafe { alloc.alloc_zeroed::<u64>().assume_init() };
+		assert_eq!(*value, 0);
+		assert_eq!(value.as_ptr() as usize % std::mem::align_of::<u64>(), 0);
+
+		let slice = unsafe { alloc.alloc_zeroed_slice::<u32>(16).assume_init() };
+		assert_eq!(slice.len(), 16);
+		assert!(slice.iter().all(|&v| v == 0));
+		assert_eq!(slice.as_ptr() as *const u32 as usize % std::mem::align_of::<u32>(), 0);
+	}
+
+	#[test]
+	fn empty_slice() {
+		let empty = ArenaBox::<[String]>::empty_slice();
+		assert_eq!(empty.len(), 0);
+		assert_eq!(empty.as_ptr() as *const String as usize % std::mem::align_of::<String>(), 0);
+		drop(empty);
+	}
+
+	#[test]
+	fn send_box() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let value = alloc.insert(5u32);
+		let value = std::thread::scope(|s| s.spawn(move || *value + 1).join().unwrap());
+		assert_eq!(value, 6);
+	}
+
+	#[test]
+	fn scope() {
+		let mut arena = Arena::new(64);
+		let length = arena.scope(|alloc| alloc.insert_str("Hello").len());
+		let string = arena.scope(|alloc| alloc.insert_str("World").to_string());
+		assert_eq!(length, 5);
+		assert_eq!(string, "World");
+	}
+
+	#[test]
+	fn insert_array() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let array = alloc.insert_array([1, 2, 3]);
+		assert_eq!(*array, [1, 2, 3]);
+
+		let slice = array.into_slice();
+		assert_eq!(slice.len(), 3);
+		assert_eq!(slice[0], 1);
+		assert_eq!(slice[2], 3);
+	}
+
+	#[cfg(feature = "bytemuck")]
+	#[test]
+	fn cast_slice() {
+		use bytemuck::PodCastError;
+
+		let mut arena = Arena::with_align(64, 4);
+		let mut alloc = arena.begin_alloc();
+		let bytes = alloc.insert_slice(&[1u8, 0, 0, 0, 2, 0, 0, 0]);
+		let words = bytes.cast_slice::<u32>().unwrap();
+		assert_eq!(&*words, &[u32::from_ne_bytes([1, 0, 0, 0]), u32::from_ne_bytes([2, 0, 0, 0])]);
+		assert_eq!(words.cast_slice::<u8>().unwrap().len(), 8);
+
+		let uneven = alloc.insert_slice(&[0u8; 6]);
+		let (uneven, error) = uneven.cast_slice::<u32>().unwrap_err();
+		assert_eq!(error, PodCastError::OutputSliceWouldHaveSlop);
+		assert_eq!(uneven.len(), 6);
+
+		// The slices are aligned to 4, so starting one byte in can't be cast to u32.
+		let _padding = alloc.insert_slice(&[0u8; 2]);
+		let _odd = alloc.insert(0u8);
+		let unaligned = alloc.insert_slice(&[3u8; 4]);
+		let (unaligned, error) = unaligned.cast_slice::<u32>().unwrap_err();
+		assert_eq!(error, PodCastError::TargetAlignmentGreaterAndInputNotAligned);
+		assert_eq!(&*unaligned, &[3; 4]);
+	}
+
+	#[cfg(feature = "rayon")]
+	#[test]
+	fn par_iter() {
+		use rayon::prelude::*;
+
+		let mut arena = Arena::new(1 << 20);
+		let mut alloc = arena.begin_alloc();
+		let mut numbers = alloc.insert_all(0..100_000u64);
+		assert_eq!(numbers.par_iter().sum::<u64>(), numbers.iter().sum::<u64>());
+
+		numbers.par_iter_mut().for_each(|v| *v *= 2);
+		assert_eq!(numbers.par_iter().sum::<u64>(), 99_999 * 100_000);
+	}
+
+	#[cfg(feature = "nightly")]
+	#[test]
+	fn coerce_unsized() {
+		use std::rc::Rc;
+
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let slice: ArenaBox<[u8]> = alloc.insert([1u8, 2, 3, 4]);
+		assert_eq!(&*slice, &[1, 2, 3, 4]);
+
+		let counter = Rc::new(());
+		let any: ArenaBox<dyn std::any::Any> = alloc.insert(counter.clone());
+		assert_eq!(Rc::strong_count(&counter), 2);
+		drop(any);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn downcast() {
+		use std::any::Any;
+		use std::rc::Rc;
+
+		let counter = Rc::new(());
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let value = alloc.insert(counter.clone());
+		let mut any = unsafe { ArenaBox::from_raw(value.into_raw() as *mut dyn Any) };
+
+		assert!(any.downcast_ref::<u32>().is_none());
+		assert!(any.downcast_mut::<Rc<()>>().is_some());
+
+		let any = any.downcast::<u32>().unwrap_err();
+		assert_eq!(Rc::strong_count(&counter), 2);
+		let value = any.downcast::<Rc<()>>().unwrap();
+		assert!(Rc::ptr_eq(&value, &counter));
+
+		drop(value);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn insert_default() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		assert_eq!(*alloc.insert_default::<u32>(), 0);
+		assert_eq!(*alloc.insert_default::<String>(), "");
+
+		let slice = alloc.insert_default_slice::<String>(4);
+		assert_eq!(slice.len(), 4);
+		assert!(slice.iter().all(|v| v.is_empty()));
+	}
+
+	#[test]
+	fn insert_default_slice_panic() {
+		use std::cell::Cell;
+		use std::panic::{catch_unwind, AssertUnwindSafe};
+
+		std::thread_local! {
+			static CREATED: Cell<usize> = const { Cell::new(0) };
+			static DROPPED: Cell<usize> = const { Cell::new(0) };
+		}
+
+		struct Counted;
+
+		impl Default for Counted {
+			fn default() -> Self {
+				let created = CREATED.with(|v| v.get());
+				assert!(created < 3, "default failed");
+				CREATED.with(|v| v.set(created + 1));
+				Counted
+			}
+		}
+
+		impl Drop for Counted {
+			fn drop(&mut self) {
+				DROPPED.with(|v| v.set(v.get() + 1));
+			}
+		}
+
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let result = catch_unwind(AssertUnwindSafe(|| {
+			alloc.insert_default_slice::<Counted>(5);
+		}));
+		assert!(result.is_err());
+		assert_eq!(CREATED.with(|v| v.get()), 3);
+		assert_eq!(DROPPED.with(|v| v.get()), 3);
+	}
+
+	#[test]
+	fn clone_in() {
+		let mut first = Arena::new(512);
+		let mut second = Arena::new(512);
+		let mut first = first.begin_alloc();
+		let mut second = second.begin_alloc();
+
+		let original = first.insert("Hello".to_string());
+		let mut clone = original.clone_in(&mut second);
+		clone.push_str(", World!");
+		assert_eq!(*original, "Hello");
+		assert_eq!(*clone, "Hello, World!");
+
+		let strings = first.insert_slice_clone(&["a".to_string(), "b".to_string()]);
+		let mut cloned_strings = strings.clone_slice_in(&mut second);
+		cloned_strings[0].push('c');
+		assert_eq!(&*strings, &["a", "b"]);
+		assert_eq!(&*cloned_strings, &["ac", "b"]);
+	}
+
+	#[test]
+	fn split_at() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let (left, right) = alloc.insert_slice(&[1, 2, 3, 4]).split_at(2);
+		assert_eq!(&*left, &[1, 2]);
+		assert_eq!(&*right, &[3, 4]);
+
+		let strings = alloc.insert_all((0..4).map(|v| v.to_string()));
+		let (left, right) = strings.split_at(4);
+		assert_eq!(left.len(), 4);
+		assert_eq!(right.len(), 0);
+	}
+
+	#[should_panic]
+	#[test]
+	fn split_at_out_of_bounds() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		alloc.insert_slice(&[1, 2, 3, 4]).split_at(5);
+	}
+
+	#[test]
+	fn ptr_eq() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let a = alloc.insert(5);
+		let b = alloc.insert(5);
+		assert!(a == b);
+		assert!(!ArenaBox::ptr_eq(&a, &b));
+		assert!(ArenaBox::ptr_eq(&a, &a));
+	}
+
+	#[test]
+	fn format_pointer() {
+		let mut arena = Arena::new(512);
+		let mut alloc = arena.begin_alloc();
+		let a = alloc.insert(5u32);
+		let b = alloc.insert(5u32);
+
+		let a_ptr = format!("{:p}", a);
+		assert!(a_ptr.starts_with("0x"));
+		assert_eq!(a_ptr, format!("{:p}", a.as_ptr()));
+		assert_ne!(a_ptr, format!("{:p}", b));
+	}
+
+	#[test]
+	fn debug() {
+		let mut arena = Arena::new(512);
+		assert!(format!("{:?}", arena).contains("512"));
+
+		let mut alloc = arena.begin_alloc();
+		alloc.insert_slice(&[0u8; 12]);
+		let debug = format!("{:?}", alloc);
+		assert!(debug.contains("used: 12"));
+		assert!(debug.contains("remaining: 500"));
+		assert!(debug.contains("capacity: 512"));
+	}
+
+	#[test]
+	fn stats() {
+		let mut arena = Arena::with_align(512, 8);
+		let mut alloc = arena.begin_alloc();
+		for i in 0..10u64 {
+			let _ = alloc.insert(i);
+		}
+
+		let stats = alloc.stats();
+		assert_eq!(stats.allocations, 10);
+		assert_eq!(stats.bytes_used, 80);
+		assert_eq!(stats.peak_bytes, 80);
+
+		let checkpoint = alloc.checkpoint();
+		let _ = alloc.insert(10u64);
+		unsafe { alloc.restore(checkpoint); }
+		let stats = alloc.stats();
+		assert_eq!(stats.allocations, 11);
+		assert_eq!(stats.bytes_used, 80);
+		assert_eq!(stats.peak_bytes, 88);
+	}
+
+	#[test]
+	fn checked_out_of_space() {
+		let mut arena = Arena::with_align(16, 8);
+		let mut alloc = arena.begin_alloc();
+		let _a = alloc.insert_checked(1u64).unwrap();
+		assert_eq!(
+			alloc.insert_checked([0u32; 4]).err(),
+			Some(AllocError::OutOfSpace { requested: 16, available: 8 }),
+		);
+		assert_eq!(
+			alloc.insert_str_checked("hello world").err(),
+			Some(AllocError::OutOfSpace { requested: 11, available: 8 }),
+		);
+		// The failures don't use up any space.
+		assert_eq!(&*alloc.insert_str_checked("hi").unwrap(), "hi");
+		assert_eq!(alloc.remaining(), 6);
+	}
+
+	#[test]
+	fn checked_layout_overflow() {
+		let mut arena = Arena::new(16);
+		let mut alloc = arena.begin_alloc();
+		assert_eq!(
+			alloc.alloc_array_checked::<u64>(usize::MAX).err(),
+			Some(AllocError::LayoutOverflow),
+		);
+	}
+
+	#[test]
+	fn contains() {
+		let mut arena_a = Arena::new(64);
+		let mut arena_b = Arena::new(64);
+		let mut alloc_a = arena_a.begin_alloc();
+		let alloc_b = arena_b.begin_alloc();
+
+		let value = alloc_a.insert(5u32);
+		let slice = alloc_a.insert_slice(&[1u8, 2, 3]);
+		assert!(alloc_a.contains(&value));
+		assert!(alloc_a.contains(&slice));
+		assert!(!alloc_b.contains(&value));
+		assert!(!alloc_b.contains(&slice));
+		let unit = alloc_a.insert(());
+		assert!(!alloc_a.contains(&unit));
+		assert!(!alloc_a.contains_ptr(&5u32 as *const u32 as *const u8));
+	}
+
+	#[test]
+	fn reset_zeroed() {
+		let mut arena = Arena::new(32);
+		{
+			let mut alloc = arena.begin_alloc();
+			alloc.insert([0xABu8; 32]).leak();
+		}
+
+		let bytes = unsafe { core::slice::from_raw_parts(arena.buffer, arena.length) };
+		assert!(bytes.iter().all(|&b| b == 0xAB));
+
+		arena.reset_zeroed();
+		let bytes = unsafe { core::slice::from_raw_parts(arena.buffer, arena.length) };
+		assert!(bytes.iter().all(|&b| b == 0));
+	}
+
+	#[test]
+	fn insert_all_chunks() {
+		let mut arena = Arena::new(4096);
+		let mut alloc = arena.begin_alloc();
+
+		// Spans several chunks, and gives back what the last chunk didn't use.
+		let slice = alloc.insert_all((0..300u32).filter(|_| true));
+		assert!(slice.iter().copied().eq(0..300));
+		assert_eq!(alloc.used(), 300 * 4);
+
+		// The last elements have to fit exactly, even though the chunks are larger.
+		let rest = alloc.remaining() / 4;
+		let fill = alloc.insert_all((0..rest as u32).filter(|_| true));
+		assert_eq!(fill.len(), rest);
+		assert_eq!(alloc.remaining(), 0);
+	}
+
+	#[test]
+	fn insert_all_gives_back_hint() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+
+		// The size hint claims 8 elements, but there are only 2.
+		let items = (0..8u32).take_while(|&v| v < 2);
+		let _slice = alloc.insert_all(IterWithHint(items, 8));
+		assert_eq!(alloc.used(), 8);
+	}
+
+	struct IterWithHint<I>(I, usize);
+
+	impl<I: Iterator> Iterator for IterWithHint<I> {
+		type Item = I::Item;
+
+		fn next(&mut self) -> Option<I::Item> {
+			self.0.next()
+		}
+
+		fn size_hint(&self) -> (usize, Option<usize>) {
+			(self.1, None)
+		}
+	}
+
+	#[test]
+	fn grow_last() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+
+		let mut slice = alloc.insert_slice(&[1u32, 2]);
+		unsafe {
+			assert!(alloc.grow_last(&mut slice, 2));
+			(slice.as_mut_ptr() as *mut u32).add(2).write(3);
+			(slice.as_mut_ptr() as *mut u32).add(3).write(4);
+		}
+		assert_eq!(&*slice, &[1, 2, 3, 4]);
+		assert_eq!(alloc.used(), 16);
+
+		let _other = alloc.insert(5u32);
+		unsafe {
+			assert!(!alloc.grow_last(&mut slice, 1));
+		}
+		assert_eq!(slice.len(), 4);
+
+		let mut last = alloc.insert_slice(&[6u32]);
+		unsafe {
+			assert!(!alloc.grow_last(&mut last, 100));
+		}
+		assert_eq!(&*last, &[6]);
+	}
+
+	#[test]
+	fn alloc_layout() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		alloc.insert(1u8).leak();
+
+		let layout = Layout::from_size_align(24, 8).unwrap();
+		let ptr = alloc.alloc_layout(layout);
+		assert_eq!(ptr as usize % 8, 0);
+		assert_eq!(alloc.used(), 32);
+		assert!(alloc.try_alloc_layout(layout).is_some());
+		assert!(alloc.try_alloc_layout(layout).is_none());
+	}
+
+	#[test]
+	fn shrink_last() {
+		use std::rc::Rc;
+
+		let counter = Rc::new(());
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+
+		let mut slice = alloc.insert_all((0..8).map(|_| counter.clone()));
+		let used = alloc.used();
+		unsafe { alloc.shrink_last(&mut slice, 3) };
+		assert_eq!(slice.len(), 3);
+		assert_eq!(Rc::strong_count(&counter), 4);
+		assert_eq!(alloc.used(), used - 5 * std::mem::size_of::<Rc<()>>());
+		assert_eq!(alloc.stats().peak_bytes, used);
+
+		// The reclaimed space is handed out again.
+		let next = alloc.insert(counter.clone());
+		assert_eq!(next.as_ptr(), (slice.as_ptr() as *const Rc<()>).wrapping_add(3));
+
+		// Slices that aren't last are only truncated.
+		let used = alloc.used();
+		unsafe { alloc.shrink_last(&mut slice, 1) };
+		assert_eq!(alloc.used(), used);
+		assert_eq!(Rc::strong_count(&counter), 3);
+	}
+
+	#[test]
+	#[should_panic]
+	fn shrink_last_longer() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut slice = alloc.insert_slice(&[1u8, 2]);
+		unsafe { alloc.shrink_last(&mut slice, 3) };
+	}
+
+	#[test]
+	fn iterate_by_reference() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut slice = alloc.insert_slice(&[1i32, 2, 3]);
+
+		let mut sum = 0;
+		for x in &slice {
+			sum += *x;
+		}
+		assert_eq!(sum, 6);
+
+		for x in slice.iter_mut() {
+			*x *= 2;
+		}
+		assert_eq!(&*slice, &[2, 4, 6]);
+	}
+
+	#[test]
+	fn slice_to_vec() {
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+
+		let numbers = alloc.insert_slice(&[1u32, 2, 3]);
+		assert_eq!(numbers.to_vec(), vec![1, 2, 3]);
+		assert_eq!(numbers.into_vec(), vec![1, 2, 3]);
+
+		let strings = alloc.insert_all((0..3).map(|v| v.to_string()));
+		assert_eq!(strings.to_vec(), vec!["0", "1", "2"]);
+		let moved: Vec<String> = strings.into_vec();
+		assert_eq!(moved, vec!["0", "1", "2"]);
+	}
+
+	#[test]
+	fn slice_into_vec_drops_once() {
+		use std::rc::Rc;
+
+		let counter = Rc::new(());
+		let vec = {
+			let mut arena = Arena::new(256);
+			let mut alloc = arena.begin_alloc();
+			let slice = alloc.insert_all((0..4).map(|_| counter.clone()));
+			slice.into_vec()
+		};
+		assert_eq!(Rc::strong_count(&counter), 5);
+		drop(vec);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn slice_eq_other_types() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let slice = alloc.insert_slice(&[1, 2, 3]);
+
+		assert_eq!(slice, [1, 2, 3]);
+		assert_eq!(slice, &[1, 2, 3][..]);
+		assert_eq!(slice, vec![1, 2, 3]);
+		assert_ne!(slice, [1, 2]);
+		assert_ne!(slice, vec![3, 2, 1]);
+	}
+
+	#[test]
+	fn swap_boxes() {
+		use std::rc::Rc;
+
+		let first = Rc::new(1);
+		let second = Rc::new(2);
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+
+		let mut a = alloc.insert(first.clone());
+		let mut b = alloc.insert(second.clone());
+		let (a_ptr, b_ptr) = (a.as_ptr(), b.as_ptr());
+
+		ArenaBox::swap(&mut a, &mut b);
+		assert_eq!((**a, **b), (2, 1));
+		assert_eq!((a.as_ptr(), b.as_ptr()), (a_ptr, b_ptr));
+
+		ArenaBox::swap_ptrs(&mut a, &mut b);
+		assert_eq!((**a, **b), (1, 2));
+		assert_eq!((a.as_ptr(), b.as_ptr()), (b_ptr, a_ptr));
+
+		drop(a);
+		assert_eq!((Rc::strong_count(&first), Rc::strong_count(&second)), (1, 2));
+		drop(b);
+		assert_eq!((Rc::strong_count(&first), Rc::strong_count(&second)), (1, 1));
+	}
+
+	#[test]
+	fn copy_slice_in() {
+		let mut arena_a = Arena::new(64);
+		let mut arena_b = Arena::new(16);
+		let mut alloc_a = arena_a.begin_alloc();
+		let mut alloc_b = arena_b.begin_alloc();
+
+		let bytes = alloc_a.insert_slice(b"interned");
+		let copy = bytes.copy_slice_in(&mut alloc_b);
+		assert_eq!(&*copy, b"interned");
+		assert!(alloc_b.contains(&copy));
+		assert!(bytes.try_copy_slice_in(&mut alloc_b).is_some());
+		assert!(bytes.try_copy_slice_in(&mut alloc_b).is_none());
+	}
+
+	#[test]
+	fn uninit_write() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+
+		let value = alloc.alloc_uninit::<String>().write("hello".to_string());
+		assert_eq!(&*value, "hello");
+
+		let mut manual = alloc.alloc_uninit::<u32>();
+		manual.as_mut().write(7);
+		let manual = unsafe { manual.assume_init() };
+		assert_eq!(*manual, 7);
+	}
+
+	#[test]
+	fn leak_unsized() {
+		let mut arena = Arena::new(128);
+		let leaked: &mut [String];
+		let string: &mut str;
+		{
+			let mut alloc = arena.begin_alloc();
+			leaked = alloc.insert_all((0..3).map(|v| v.to_string())).leak();
+			string = alloc.insert_str("leaked").leak();
+		}
+		// The borrows outlive the allocator, but not the arena.
+		assert_eq!(leaked.len(), 3);
+		assert_eq!(leaked[2], "2");
+		assert_eq!(string, "leaked");
+		for item in leaked.iter_mut() {
+			unsafe { core::ptr::drop_in_place(item) };
+		}
+	}
+
+	#[test]
+	fn insert_bytes() {
+		let mut arena = Arena::new(8192);
+		let mut alloc = arena.begin_alloc();
+
+		let packet: Vec<u8> = (0..4096).map(|v| (v * 7) as u8).collect();
+		let bytes = alloc.insert_bytes(&packet);
+		assert_eq!(bytes, packet);
+		assert!(alloc.try_insert_bytes(&packet).is_some());
+		assert!(alloc.try_insert_bytes(&packet).is_none());
+	}
+
+	#[test]
+	fn poll_pinned_future() {
+		use core::future::Future;
+		use core::pin::Pin;
+		use core::task::{Context, Poll, Waker};
+
+		struct YieldOnce(bool);
+
+		impl Future for YieldOnce {
+			type Output = ();
+
+			fn poll(mut self: Pin<&mut Self>, _: &mut Context) -> Poll<()> {
+				if self.0 {
+					Poll::Ready(())
+				} else {
+					self.0 = true;
+					Poll::Pending
+				}
+			}
+		}
+
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		// Holds a reference to a local across an await, so it can't be Unpin.
+		let future = alloc.insert(async {
+			let value = 5;
+			let reference = &value;
+			YieldOnce(false).await;
+			*reference + 1
+		});
+		let mut future = unsafe { future.into_pin() };
+
+		let mut cx = Context::from_waker(Waker::noop());
+		assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+		assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(6));
+	}
+
+	#[test]
+	fn forget() {
+		use std::rc::Rc;
+
+		let counter = Rc::new(());
+		let mut arena = Arena::with_align(64, 8);
+		{
+			let mut alloc = arena.begin_alloc();
+			alloc.insert(counter.clone()).forget();
+			assert_eq!(Rc::strong_count(&counter), 2);
+		}
+
+		// The memory is reused, even though the Rc was never dropped.
+		let mut alloc = arena.begin_alloc();
+		assert_eq!(alloc.insert(0u64).as_ptr() as *mut u8, alloc.start);
+	}
+
+	#[test]
+	fn insert_aligned() {
+		let mut arena = Arena::new(8192);
+		let mut alloc = arena.begin_alloc();
+		let _byte = alloc.insert(1u8);
+
+		let page = alloc.insert_aligned([7u8; 16], 4096);
+		assert_eq!(page.as_ptr() as usize % 4096, 0);
+		assert_eq!(*page, [7; 16]);
+
+		// The alignment of the type is kept if it's larger.
+		let value = alloc.insert_aligned(5u64, 1);
+		assert_eq!(value.as_ptr() as usize % 8, 0);
+	}
+
+	#[test]
+	#[should_panic(expected = "Allocation failed")]
+	fn new_invalid_length() {
+		// This can never be a valid layout, so it panics instead of reporting running out of
+		// memory.
+		let _arena = Arena::new(usize::MAX);
+	}
+
+	/// Running out of memory calls ``handle_alloc_error``, which aborts the process, so this has
+	/// to be checked manually with ``cargo test -- --ignored new_out_of_memory``. It should print
+	/// "memory allocation of ... bytes failed" and abort.
+	#[test]
+	#[ignore = "aborts the process"]
+	fn new_out_of_memory() {
+		let _arena = Arena::new(isize::MAX as usize);
+	}
+
+	#[test]
+	fn slice_ranges() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut slice = alloc.insert_slice(&[0, 1, 2, 3, 4]);
+
+		assert_eq!(slice.slice(1..3), &[1, 2]);
+		assert_eq!(slice.slice(1..=3), &[1, 2, 3]);
+		assert_eq!(slice.slice(..2), &[0, 1]);
+		assert_eq!(slice.slice(3..), &[3, 4]);
+		assert_eq!(slice.slice(..), &[0, 1, 2, 3, 4]);
+		assert_eq!(slice.slice(5..), &[] as &[i32]);
+
+		slice.slice_mut(..=1).fill(9);
+		assert_eq!(&*slice, &[9, 9, 2, 3, 4]);
+	}
+
+	#[test]
+	#[should_panic(expected = "range end 6 is out of bounds for a slice of length 5")]
+	fn slice_out_of_range() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let slice = alloc.insert_slice(&[0, 1, 2, 3, 4]);
+		let _ = slice.slice(2..=5);
+	}
+
+	#[test]
+	#[should_panic(expected = "range starts at 3 but ends at 2")]
+	fn slice_backwards() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let slice = alloc.insert_slice(&[0, 1, 2, 3, 4]);
+		#[allow(clippy::reversed_empty_ranges)]
+		let _ = slice.slice(3..2);
+	}
+
+	#[test]
+	fn insert_with_panic_rolls_back() {
+		use std::panic::{catch_unwind, AssertUnwindSafe};
+
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let _first = alloc.insert(1u8);
+		let remaining = alloc.remaining();
+		let allocations = alloc.stats().allocations;
+
+		let result = catch_unwind(AssertUnwindSafe(|| {
+			let _ = alloc.insert_with(|| -> [u64; 4] { panic!("no value") });
+		}));
+		assert!(result.is_err());
+		assert_eq!(alloc.remaining(), remaining);
+		assert_eq!(alloc.stats().allocations, allocations);
+	}
+
+	#[test]
+	fn insert_all_counts_once() {
+		let mut arena = Arena::new(4096);
+		let mut alloc = arena.begin_alloc();
+		let _first = alloc.insert(1u8);
+
+		// The filter makes the size hint 0, so the elements are reserved in several chunks.
+		let odd = alloc.insert_all((0..600u32).filter(|v| v % 2 == 1));
+		assert_eq!(odd.len(), 300);
+		assert_eq!(alloc.stats().allocations, 2);
+
+		// A failed insert doesn't count at all.
+		assert!(alloc.try_insert_all((0..2000u32).filter(|_| true)).is_none());
+		assert_eq!(alloc.stats().allocations, 2);
+	}
+
+	#[test]
+	fn insert_iter_exact() {
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+
+		let slice = alloc.insert_iter_exact((0..10u32).map(|v| v * 2));
+		assert!(slice.iter().copied().eq((0..10).map(|v| v * 2)));
+		assert_eq!(alloc.used(), 40);
+	}
+
+	#[test]
+	fn insert_iter_exact_lying() {
+		use std::rc::Rc;
+
+		struct Lying<I>(I, usize);
+
+		impl<I: Iterator> Iterator for Lying<I> {
+			type Item = I::Item;
+
+			fn next(&mut self) -> Option<I::Item> {
+				self.0.next()
+			}
+
+			fn size_hint(&self) -> (usize, Option<usize>) {
+				(self.1, Some(self.1))
+			}
+		}
+
+		impl<I: Iterator> ExactSizeIterator for Lying<I> {}
+
+		let counter = Rc::new(());
+		let mut arena = Arena::with_align(256, 8);
+		let mut alloc = arena.begin_alloc();
+
+		// Yields fewer than it claims.
+		let short = alloc.insert_iter_exact(Lying((0..3).map(|_| counter.clone()), 8));
+		assert_eq!(short.len(), 3);
+		assert_eq!(alloc.used(), 3 * std::mem::size_of::<Rc<()>>());
+
+		// Yields more than it claims, the rest are left in the iterator.
+		let mut long = Lying(0..10u32, 4);
+		let slice = alloc.insert_iter_exact(&mut long);
+		assert_eq!(&*slice, &[0, 1, 2, 3]);
+		assert_eq!(long.next(), Some(4));
+
+		drop(short);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn into_value_drops_once() {
+		use std::rc::Rc;
 
-		// TODO: We may want to be less pedantic here for performance reasons.
-		// (layout.align() - 1) is fine because align is guaranteed to not be zero.
-		self.head = (
-			(self.head as usize).checked_add(layout.align() - 1)?
-			& !(layout.align() - 1)
-		) as *mut u8;
+		let counter = Rc::new(());
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
 
-		// self.last is always larger than self.head, so this will never overflows.
-		if self.last as usize - self.head as usize <= layout.size() {
-			return None;
-		}
+		let value = alloc.insert(counter.clone()).into_value();
+		assert_eq!(Rc::strong_count(&counter), 2);
+		drop(value);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
 
-		let value = self.head;
-		// SAFETY: We know that head + size does not go past the allocation point, and the allocation
-		// has to not overflow.
-		unsafe {
-			self.head = self.head.add(layout.size());
+	#[test]
+	fn new_page_aligned() {
+		let page_size = Arena::page_size();
+		assert!(page_size.is_power_of_two());
+
+		let arena = Arena::new_page_aligned(100);
+		assert_eq!(arena.buffer as usize % page_size, 0);
+		assert_eq!(arena.length, page_size);
+		assert_eq!(arena.align, page_size);
+
+		let arena = Arena::new_page_aligned(page_size + 1);
+		assert_eq!(arena.length, page_size * 2);
+	}
+
+	#[test]
+	fn str_as_bytes() {
+		use std::io::Write;
+
+		fn send(socket: &mut impl Write, message: impl AsRef<[u8]>) {
+			socket.write_all(message.as_ref()).unwrap();
 		}
-		Some(value)
+
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut socket = Vec::new();
+		send(&mut socket, alloc.insert_str("hello "));
+		send(&mut socket, alloc.insert_str("world"));
+		assert_eq!(socket, b"hello world");
+
+		let string = alloc.insert_str("as str");
+		let as_str: &str = string.as_ref();
+		assert_eq!(as_str, "as str");
 	}
-}
 
-#[cfg(test)]
-mod tests {
-	use super::*;
+	#[test]
+	fn insert_all_into_iter() {
+		let mut arena = Arena::new(128);
+		let mut alloc = arena.begin_alloc();
+
+		assert_eq!(alloc.insert_all([1, 2, 3]), [1, 2, 3]);
+		assert_eq!(alloc.insert_all(vec![4, 5]), [4, 5]);
+		assert_eq!(alloc.insert_all(&[6, 7]), [&6, &7]);
+	}
 
 	#[test]
-	fn create_arena() {
-		let _arena = Arena::new(512);
+	fn cast() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+
+		let bytes = alloc.insert_aligned([1u8, 2, 3, 4], std::mem::align_of::<u32>());
+		let word = unsafe { bytes.cast::<u32>() };
+		assert_eq!(*word, u32::from_ne_bytes([1, 2, 3, 4]));
+
+		let bytes = unsafe { word.cast::<[u8; 4]>() };
+		assert_eq!(*bytes, [1, 2, 3, 4]);
 	}
 
 	#[test]
-	fn allocate_numbers() {
-		let mut arena = Arena::new(512);
-		let mut allocator = arena.begin_alloc();
+	fn as_ptr_range() {
+		let mut arena = Arena::new(128);
+		let range = arena.as_ptr_range();
+		assert_eq!(range.end as usize - range.start as usize, 128);
 
-		let hello = allocator.insert(5.2);
-		allocator.insert(5);
+		let mut alloc = arena.begin_alloc();
+		let value = alloc.insert(5u32);
+		assert!(range.contains(&(value.as_ptr() as *const u8)));
+		assert!(!range.contains(&(&5u32 as *const u32 as *const u8)));
+	}
 
-		// Without this drop, the next ``area.begin()`` will not work, because the drop call at the
-		// end of the scope will try to drop hello, but the memory might have been overwritten.
-		std::mem::drop(hello);
+	#[test]
+	fn zeroize() {
+		let mut arena = Arena::new(32);
+		{
+			let mut alloc = arena.begin_alloc();
+			alloc.insert_bytes(b"a secret key that is 32 bytes!!!").leak();
+		}
 
-		let mut allocator = arena.begin_alloc();
+		arena.zeroize();
+		let bytes = unsafe { core::slice::from_raw_parts(arena.buffer, arena.length) };
+		assert!(bytes.iter().all(|&b| b == 0));
+	}
 
-		let my_string = allocator.insert(format!("Hello, World!"));
-		println!("{}", my_string);
+	#[test]
+	fn begin_alloc_at() {
+		let mut arena = Arena::new(128);
+		let header = arena.as_mut_ptr();
+		let mut alloc = arena.begin_alloc_at(64);
+		assert_eq!(alloc.capacity(), 64);
+
+		let value = alloc.insert(5u8);
+		assert!(value.as_ptr() as usize >= header as usize + 64);
+		unsafe { header.write_bytes(0xFF, 64) };
+		assert_eq!(*value, 5);
 	}
 
 	#[test]
-	fn enum_testing() {
-		#[derive(Debug)]
-		enum Ast<'a> {
-			Number(i64),
-			BinaryOperator {
-				left:  ArenaBox<'a, Ast<'a>>,
-				right: ArenaBox<'a, Ast<'a>>,
-				operator: char,
-			}
+	#[should_panic(expected = "offset is out of bounds")]
+	fn begin_alloc_at_out_of_bounds() {
+		let mut arena = Arena::new(64);
+		let _alloc = arena.begin_alloc_at(64);
+	}
+
+	#[test]
+	fn arena_split_at() {
+		let arena = Arena::with_align(1024, 8);
+		let (mut first, mut second) = arena.split_at(512);
+		assert_eq!((first.length, second.length), (512, 512));
+		assert_eq!(first.as_ptr_range().end, second.as_ptr_range().start);
+
+		let handle = std::thread::spawn(move || {
+			let mut alloc = second.begin_alloc();
+			let slice = alloc.insert_all(0..64u64);
+			assert_eq!(alloc.remaining(), 0);
+			slice.iter().sum::<u64>()
+		});
+		{
+			let mut alloc = first.begin_alloc();
+			let slice = alloc.insert_all(0..64u64);
+			assert_eq!(alloc.remaining(), 0);
+			assert_eq!(slice.iter().sum::<u64>(), handle.join().unwrap());
 		}
 
-		fn parse_stuff<'a>(arena: &mut ArenaAlloc<'a>) -> ArenaBox<'a, Ast<'a>> {
-			let left  = arena.insert(Ast::Number(125));
-			let right = arena.insert(Ast::Number(24));
+		// Splitting a half again shares the same block.
+		let (a, b) = first.split_at(500);
+		assert_eq!(b.align, 4);
+		drop(a);
+		assert_eq!(b.into_vec().capacity(), 12);
+	}
 
-			arena.insert(Ast::BinaryOperator { left, right, operator: '+' })
+	#[test]
+	fn split_first_last() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+
+		let mut tokens = alloc.insert_slice(&[1, 2, 3]);
+		assert_eq!(tokens.split_first(), Some((&1, &[2, 3][..])));
+		assert_eq!(tokens.split_last(), Some((&3, &[1, 2][..])));
+
+		if let Some((first, rest)) = tokens.split_first_mut() {
+			*first = rest[0] + rest[1];
+		}
+		if let Some((last, _)) = tokens.split_last_mut() {
+			*last = 0;
 		}
+		assert_eq!(tokens, [5, 2, 0]);
 
-		let mut arena = Arena::new(1024);
-		let mut arena = arena.begin_alloc();
-		let ast = parse_stuff(&mut arena);
+		let mut empty = ArenaBox::<[i32]>::empty_slice();
+		assert_eq!(empty.split_first(), None);
+		assert_eq!(empty.split_last(), None);
+		assert!(empty.split_first_mut().is_none());
+		assert!(empty.split_last_mut().is_none());
+	}
 
-		println!("{:?}", ast);
+	#[cfg(feature = "nightly")]
+	#[test]
+	fn coerce_unsized_dyn_display() {
+		use core::cell::Cell;
+
+		struct Loud<'c>(&'c Cell<u32>, u32);
+
+		impl fmt::Display for Loud<'_> {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				write!(f, "loud {}", self.1)
+			}
+		}
+
+		impl Drop for Loud<'_> {
+			fn drop(&mut self) {
+				self.0.set(self.0.get() + 1);
+			}
+		}
+
+		let drops = Cell::new(0);
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let values: [ArenaBox<dyn fmt::Display>; 3] = [
+			alloc.insert(Loud(&drops, 1)),
+			alloc.insert(5i32),
+			alloc.insert("text"),
+		];
+		assert_eq!(values[0].to_string(), "loud 1");
+		assert_eq!(values[1].to_string(), "5");
+		assert_eq!(values[2].to_string(), "text");
+		drop(values);
+		assert_eq!(drops.get(), 1);
 	}
 
 	#[test]
-	fn mass_allocate() {
-		let mut arena = Arena::new(9000);
-		let mut insert = arena.begin_alloc();
+	fn align_to() {
+		let mut arena = Arena::with_align(64, 64);
+		let mut alloc = arena.begin_alloc();
+		alloc.align_to(8);
+		assert_eq!(alloc.used(), 0);
 
-		let _: Vec<_> = (0..1000u64).map(|v| insert.insert(v)).collect();
+		let _ = alloc.insert_bytes(b"abc");
+		alloc.align_to(8);
+		assert_eq!(alloc.used(), 8);
+		assert_eq!(alloc.stats().allocations, 1);
+		alloc.align_to(1);
+		assert_eq!(alloc.used(), 8);
+
+		let _ = alloc.insert(1u8);
+		alloc.align_to(128);
+		assert_eq!(alloc.remaining(), 0);
 	}
 
+	#[test]
 	#[should_panic]
+	fn align_to_not_power_of_two() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		alloc.align_to(3);
+	}
+
 	#[test]
-	fn over_allocate() {
-		let mut arena = Arena::new(16);
-		let mut insert = arena.begin_alloc();
-		insert.insert(5u64);
-		insert.insert(5u64);
-		insert.insert(5u64);
+	fn fill() {
+		use std::rc::Rc;
+		use std::string::String;
+
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let mut numbers = alloc.insert_all(0..4i32);
+		numbers.fill(7);
+		assert_eq!(&*numbers, &[7, 7, 7, 7]);
+		let mut next = 0;
+		numbers.fill_with(|| { next += 1; next });
+		assert_eq!(&*numbers, &[1, 2, 3, 4]);
+
+		let counter = Rc::new(());
+		let mut strings = alloc.insert_all((0..3).map(|_| (String::from("old"), counter.clone())));
+		assert_eq!(Rc::strong_count(&counter), 4);
+		let other = Rc::new(());
+		strings.fill((String::from("new"), other.clone()));
+		assert_eq!(Rc::strong_count(&counter), 1);
+		assert_eq!(Rc::strong_count(&other), 4);
+		assert!(strings.iter().all(|(s, _)| s == "new"));
+		strings.fill_with(|| (String::from("newer"), counter.clone()));
+		assert_eq!(Rc::strong_count(&other), 1);
+		assert_eq!(strings[2].0, "newer");
 	}
-	
+
 	#[test]
-	fn insert_all() {
-		let mut arena = Arena::new(1400);
+	fn capacity_for() {
+		#[repr(align(32))]
+		struct Aligned(#[allow(dead_code)] u8);
 
-		{
-			let mut alloc = arena.begin_alloc();
+		let mut arena = Arena::new(100);
+		assert_eq!(arena.capacity_for::<u8>(), 100);
+		assert_eq!(arena.capacity_for::<()>(), usize::MAX);
+		let fit = arena.capacity_for::<u64>();
+		assert!(fit >= 11);
+		let mut alloc = arena.begin_alloc();
+		for _ in 0..fit {
+			let _ = alloc.insert(0u64);
+		}
 
-			let slice = alloc.insert_all(0..100u64);
-			// assert_eq!(slice.len(), 100);
+		let fit = arena.capacity_for::<Aligned>();
+		assert!(fit >= 2);
+		let mut alloc = arena.begin_alloc();
+		for _ in 0..fit {
+			let _ = alloc.insert(Aligned(0));
+		}
 
-			for i in 0..100u64 {
-				assert_eq!(slice[i as usize], i);
+		let arena = Arena::with_align(100, 32);
+		assert_eq!(arena.capacity_for::<u64>(), 12);
+		assert_eq!(arena.capacity_for::<Aligned>(), 3);
+		assert_eq!(Arena::new(8).capacity_for::<[u8; 16]>(), 0);
+	}
+
+	#[test]
+	fn into_chunks() {
+		use std::rc::Rc;
+
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let chunks = alloc.insert_all(1..=10u32).into_chunks(3);
+		assert_eq!(chunks.len(), 4);
+		assert_eq!(&*chunks[0], &[1, 2, 3]);
+		assert_eq!(&*chunks[2], &[7, 8, 9]);
+		assert_eq!(&*chunks[3], &[10]);
+
+		let counter = Rc::new(());
+		let chunks = alloc.insert_all((0..10).map(|_| counter.clone())).into_chunks(3);
+		assert_eq!(Rc::strong_count(&counter), 11);
+		drop(chunks);
+		assert_eq!(Rc::strong_count(&counter), 1);
+
+		assert!(ArenaBox::<[u8]>::empty_slice().into_chunks(4).is_empty());
+	}
+
+	#[test]
+	fn compare_different_types() {
+		use std::string::String;
+
+		#[derive(PartialEq, PartialOrd)]
+		struct Meters(f64);
+
+		impl PartialEq<f64> for Meters {
+			fn eq(&self, other: &f64) -> bool {
+				self.0 == *other
+			}
+		}
+
+		impl PartialOrd<f64> for Meters {
+			fn partial_cmp(&self, other: &f64) -> Option<core::cmp::Ordering> {
+				self.0.partial_cmp(other)
 			}
 		}
 
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let owned = alloc.insert(String::from("hello"));
+		let borrowed = alloc.insert("hello");
+		assert!(owned == borrowed);
+		assert!(owned != alloc.insert("world"));
+
+		let distance = alloc.insert(Meters(2.0));
+		assert!(distance == alloc.insert(2.0));
+		assert!(distance < alloc.insert(3.0));
+		assert!(distance > alloc.insert(Meters(1.0)));
+	}
+
+	#[test]
+	fn alloc_slice_uninit() {
+		use std::string::{String, ToString};
+
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let mut slice = alloc.alloc_slice_uninit::<String>(3);
+		assert_eq!(slice.len(), 3);
+		for (i, element) in slice.iter_mut().enumerate() {
+			element.write(i.to_string());
+		}
+		// SAFETY: Every element was written.
+		let slice = unsafe { slice.assume_init() };
+		assert_eq!(&*slice, &["0", "1", "2"]);
+
+		assert!(alloc.try_alloc_slice_uninit::<u64>(64).is_none());
+		assert_eq!(alloc.alloc_slice_uninit::<u64>(0).len(), 0);
+	}
+
+	#[test]
+	fn borrowed_keys() {
+		use std::collections::{HashMap, HashSet};
+
+		// The generic Borrow<T> impl already covers Borrow<str> and Borrow<[T]>, and Hash and Eq
+		// forward to the contents, so boxes work as keys that can be looked up by reference.
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let mut map = HashMap::new();
+		for (i, key) in ["one", "two", "three"].iter().enumerate() {
+			map.insert(alloc.insert_str(key), i);
+		}
+		assert_eq!(map.get("two"), Some(&1));
+		assert_eq!(map.get("four"), None);
+
+		let mut set = HashSet::new();
+		set.insert(alloc.insert_slice(&[1u8, 2]));
+		set.insert(alloc.insert_slice(&[3u8]));
+		assert!(set.contains(&[3u8][..]));
+		assert!(!set.contains(&[1u8][..]));
+	}
+
+	#[test]
+	fn remaining_for() {
+		let mut arena = Arena::with_align(64, 8);
+		let mut alloc = arena.begin_alloc();
+		assert_eq!(alloc.remaining_for::<u64>(), 8);
+		let _ = alloc.insert(1u8);
+		assert_eq!(alloc.remaining_for::<u8>(), 63);
+		assert_eq!(alloc.remaining_for::<u64>(), 7);
+		assert_eq!(alloc.remaining_for::<()>(), usize::MAX);
+
+		for _ in 0..7 {
+			let _ = alloc.insert(0u64);
+		}
+		assert_eq!(alloc.remaining_for::<u64>(), 0);
+		assert!(alloc.try_insert(0u64).is_none());
+
+		let mut arena = Arena::with_align(12, 8);
+		let mut alloc = arena.begin_alloc();
+		let _ = alloc.insert(1u8);
+		assert_eq!(alloc.remaining_for::<u64>(), 0);
+	}
+
+	#[test]
+	fn insert_indexed() {
+		use core::cell::Cell;
+
+		struct Node {
+			offset: usize,
+			other: Cell<usize>,
+		}
+
+		let mut arena = Arena::with_align(64, 8);
+		let mut alloc = arena.begin_alloc();
+		let _ = alloc.insert(1u8);
+		let (a_offset, a) = alloc.insert_indexed(|offset| Node { offset, other: Cell::new(0) });
+		let (b_offset, b) =
+			alloc.insert_indexed(|offset| Node { offset, other: Cell::new(a_offset) });
+		a.other.set(b_offset);
+
+		assert_eq!(a_offset, 8);
+		assert_eq!(a.offset, alloc.offset_of(&a));
+		assert_eq!(b.offset, alloc.offset_of(&b));
+		assert_eq!(a.other.get(), b.offset);
+		assert_eq!(b.other.get(), a.offset);
+
+		let (offset, _) = alloc.insert_indexed(|_| ());
+		assert_eq!(offset, alloc.used());
+		assert!(alloc.try_insert_indexed(|_| [0u8; 64]).is_none());
+	}
+
+	#[test]
+	fn raw_parts_round_trip() {
+		use std::rc::Rc;
+
+		let counter = Rc::new(());
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let slice = alloc.insert_all((0..4).map(|_| counter.clone()));
+		let (ptr, len) = slice.into_raw_parts();
+		assert_eq!(Rc::strong_count(&counter), 5);
+
+		// SAFETY: The parts came from a box that was taken apart.
+		let slice = unsafe { ArenaBox::from_raw_parts(ptr, len) };
+		assert_eq!(slice.len(), 4);
+		assert!(Rc::ptr_eq(&slice[3], &counter));
+		drop(slice);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn into_raw_parts() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let (ptr, len) = alloc.insert_slice(&[1u16, 2, 3]).into_raw_parts();
+		assert_eq!(len, 3);
+		// SAFETY: The pointer is valid for len elements, which were forgotten by the box.
+		unsafe {
+			assert_eq!(*ptr.add(2), 3);
+			let slice = ArenaBox::from_raw_parts(ptr, len);
+			assert_eq!(&*slice, &[1, 2, 3]);
+		}
+	}
+
+	#[test]
+	fn grow() {
+		let mut arena = Arena::with_align(64, 16);
 		{
 			let mut alloc = arena.begin_alloc();
-			let slice = alloc.insert_all((0..50).map(|v| format!("{}", v)));
-			assert_eq!(slice.len(), 50);
+			assert!(alloc.try_insert([0u8; 128]).is_none());
+		}
 
-			for i in 0..50 {
-				assert_eq!(slice[i].parse(), Ok(i));
+		arena.grow(256);
+		assert_eq!(arena.as_ptr_range().end as usize - arena.as_ptr_range().start as usize, 256);
+		assert_eq!(arena.as_ptr_range().start as usize % 16, 0);
+		let mut alloc = arena.begin_alloc();
+		let value = alloc.insert([7u8; 256]);
+		assert_eq!(value[255], 7);
+		assert_eq!(alloc.remaining(), 0);
+
+		let (mut first, second) = Arena::new(64).split_at(32);
+		first.grow(100);
+		assert_eq!(first.begin_alloc().capacity(), 100);
+		assert_eq!(first.capacity_for::<u8>(), 100);
+		drop(second);
+		let _ = first.begin_alloc().insert([1u8; 100]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn grow_smaller() {
+		let mut arena = Arena::new(64);
+		arena.grow(32);
+	}
+
+	#[test]
+	fn shrink() {
+		let mut arena = Arena::new(1024);
+		let _ = arena.begin_alloc().insert([0u8; 512]);
+		arena.shrink(128);
+
+		let mut alloc = arena.begin_alloc();
+		assert_eq!(alloc.capacity(), 128);
+		assert!(alloc.try_insert([0u8; 129]).is_none());
+		let _ = alloc.insert([0u8; 128]);
+		assert!(alloc.try_insert(0u8).is_none());
+
+		let (mut first, _second) = Arena::new(64).split_at(32);
+		first.shrink(16);
+		assert_eq!(first.begin_alloc().capacity(), 16);
+	}
+
+	#[test]
+	fn reallocate_split_same_length() {
+		let (mut first, second) = Arena::new(64).split_at(32);
+		let old = first.as_ptr_range();
+		first.grow(32);
+		let new = first.as_ptr_range();
+		// The block is still alive because of the other half, so the new buffer can't overlap it.
+		assert!(new.start >= second.as_ptr_range().end || new.end <= old.start);
+		assert_eq!(new.end as usize - new.start as usize, 32);
+		drop(second);
+		let _ = first.begin_alloc().insert([1u8; 32]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn shrink_to_zero() {
+		let mut arena = Arena::new(64);
+		arena.shrink(0);
+	}
+
+	#[test]
+	#[should_panic]
+	fn shrink_larger() {
+		let mut arena = Arena::new(64);
+		arena.shrink(65);
+	}
+
+	#[test]
+	fn replace() {
+		use core::cell::RefCell;
+		use std::vec::Vec;
+
+		struct Push<'d>(&'d RefCell<Vec<u32>>, u32);
+
+		impl Drop for Push<'_> {
+			fn drop(&mut self) {
+				self.0.borrow_mut().push(self.1);
 			}
 		}
+
+		let drops = RefCell::new(Vec::new());
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut value = alloc.insert(Push(&drops, 1));
+		let ptr = value.as_ptr();
+
+		let old = value.replace(Push(&drops, 2));
+		assert_eq!(old.1, 1);
+		assert_eq!(value.1, 2);
+		assert_eq!(value.as_ptr(), ptr);
+		assert!(drops.borrow().is_empty());
+
+		drop(old);
+		assert_eq!(*drops.borrow(), [1]);
+		drop(value);
+		assert_eq!(*drops.borrow(), [1, 2]);
 	}
 
 	#[test]
-	fn insert_all_fail() {
-		let mut arena = Arena::new(50);
+	fn pointer_layout() {
+		use core::mem::{align_of, size_of};
+
+		assert_eq!(size_of::<ArenaBox<u32>>(), size_of::<*mut u32>());
+		assert_eq!(align_of::<ArenaBox<u32>>(), align_of::<*mut u32>());
+		assert_eq!(size_of::<ArenaBox<[u8]>>(), size_of::<*mut [u8]>());
+		assert_eq!(size_of::<Option<ArenaBox<u8>>>(), size_of::<Option<*mut u8>>());
+
+		let mut arena = Arena::new(64);
 		let mut alloc = arena.begin_alloc();
-		let slice = alloc.try_insert_all((0..50).map(|v| format!("{}", v)));
-		assert!(slice.is_none());
+		let value = alloc.insert(5u32);
+		let ptr = value.as_ptr();
+		// SAFETY: The box is a transparent wrapper around a pointer, and it's not dropped twice,
+		// since transmute consumes it.
+		let raw: *mut u32 = unsafe { core::mem::transmute(value) };
+		assert_eq!(raw as *const u32, ptr);
+		// SAFETY: The pointer came from the box, which was consumed.
+		let value = unsafe { ArenaBox::from_raw(raw) };
+		assert_eq!(*value, 5);
 	}
 
 	#[test]
-	fn insert_slice() {
-		let mut arena = Arena::new(5600);
+	fn reserved_space_not_in_peak() {
+		let mut arena = Arena::new(1024);
 		let mut alloc = arena.begin_alloc();
-		let arena_slice = alloc.insert_slice(&[1, 2, 3, 4]);
+		// The filter has no lower bound, so insert_all reserves ahead and gives the rest back.
+		let evens = alloc.insert_all((0..10u64).filter(|x| x % 2 == 0));
+		assert_eq!(evens.len(), 5);
+		let stats = alloc.stats();
+		assert_eq!(stats.bytes_used, 40);
+		assert_eq!(stats.peak_bytes, 40);
+	}
 
-		assert_eq!(arena_slice.len(), 4);
-		assert_eq!(arena_slice[0], 1);
-		assert_eq!(arena_slice[1], 2);
-		assert_eq!(arena_slice[2], 3);
-		assert_eq!(arena_slice[3], 4);
+	#[test]
+	fn offset_from_buffer_start() {
+		let mut arena = Arena::with_align(64, 8);
+		let base = arena.as_mut_ptr() as usize;
+		let mut alloc = arena.begin_alloc_at(16);
+		let (offset, value) = alloc.insert_indexed(|_| 1u32);
+		assert_eq!(offset, 16);
+		assert_eq!(value.as_ptr() as usize, base + offset);
+
+		let mut frame = alloc.frame();
+		let (inner, inner_value) = frame.insert_indexed(|_| 2u32);
+		assert_eq!(inner, 20);
+		assert_eq!(inner_value.as_ptr() as usize, base + inner);
+		assert_eq!(frame.offset_of(&inner_value), inner);
+		// Boxes from before the frame still have their offsets.
+		assert_eq!(frame.offset_of(&value), offset);
+	}
+
+	#[test]
+	fn arena_peak_bytes() {
+		let mut arena = Arena::new(512);
+		assert_eq!(arena.peak_bytes(), 0);
+		let _ = arena.begin_alloc().insert([0u8; 200]);
+		assert_eq!(arena.peak_bytes(), 200);
+
+		// A smaller batch doesn't lower the peak, even though its own stats start over.
+		let mut alloc = arena.begin_alloc();
+		let _ = alloc.insert([0u8; 10]);
+		assert_eq!(alloc.stats().peak_bytes, 10);
+		assert_eq!(arena.peak_bytes(), 200);
+
+		// Rolled back space still counts.
+		let mut alloc = arena.begin_alloc();
+		let checkpoint = alloc.checkpoint();
+		let _ = alloc.insert([0u8; 300]);
+		unsafe { alloc.restore(checkpoint) };
+		assert_eq!(arena.peak_bytes(), 300);
+
+		// So does space used by frames.
+		let mut alloc = arena.begin_alloc();
+		let _ = alloc.insert([0u8; 100]);
+		let _ = alloc.frame().insert([0u8; 250]);
+		assert_eq!(arena.peak_bytes(), 350);
+
+		let _ = arena.begin_alloc_at(400).insert([0u8; 50]);
+		assert_eq!(arena.peak_bytes(), 450);
+
+		arena.shrink(256);
+		assert_eq!(arena.peak_bytes(), 450);
 	}
 }
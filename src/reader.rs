@@ -0,0 +1,189 @@
+use core::convert::TryFrom;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+use crate::ArenaBox;
+
+/// Reads from a boxed byte slice, keeping track of how far it has read, like a
+/// [Cursor](std::io::Cursor). Create one with [ArenaBox::reader].
+///
+/// A boxed byte slice can't implement [Read] itself, since it would overlap with the
+/// implementation for boxed readers, and it would have nowhere to store the position.
+///
+/// ```
+/// use std::io::Read;
+///
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let mut reader = alloc.insert_bytes(b"hello").reader();
+/// let mut buf = [0; 3];
+/// reader.read_exact(&mut buf).unwrap();
+/// assert_eq!(&buf, b"hel");
+/// ```
+pub struct ArenaReader<'a> {
+	// The position may be past the end of bytes after seeking, in which case there is nothing
+	// left to read.
+	bytes: ArenaBox<'a, [u8]>,
+	position: usize,
+}
+
+impl<'a> ArenaReader<'a> {
+	/// Creates a reader that starts at the beginning of the bytes.
+	pub(crate) fn new(bytes: ArenaBox<'a, [u8]>) -> Self {
+		Self { bytes, position: 0 }
+	}
+
+	/// Returns how many bytes have been read.
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
+	/// Returns the bytes that haven't been read yet.
+	pub fn remaining_slice(&self) -> &[u8] {
+		self.bytes.get(self.position..).unwrap_or(&[])
+	}
+
+	/// Returns the boxed bytes, including the ones that have been read.
+	pub fn into_inner(self) -> ArenaBox<'a, [u8]> {
+		self.bytes
+	}
+}
+
+impl Read for ArenaReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let read = self.remaining_slice().read(buf)?;
+		self.position += read;
+		Ok(read)
+	}
+
+	fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+		self.remaining_slice().read_exact(buf)?;
+		self.position += buf.len();
+		Ok(())
+	}
+}
+
+impl BufRead for ArenaReader<'_> {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		Ok(self.remaining_slice())
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.position += amt;
+	}
+}
+
+impl Seek for ArenaReader<'_> {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		let position = match pos {
+			SeekFrom::Start(position) => usize::try_from(position).ok(),
+			SeekFrom::End(offset) => offset_position(self.bytes.len(), offset),
+			SeekFrom::Current(offset) => offset_position(self.position, offset),
+		};
+
+		match position {
+			Some(position) => {
+				self.position = position;
+				Ok(position as u64)
+			}
+			None => Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"invalid seek to a negative or overflowing position",
+			)),
+		}
+	}
+
+	fn stream_position(&mut self) -> io::Result<u64> {
+		Ok(self.position as u64)
+	}
+}
+
+/// Offsets a position, returning None if it would be negative or overflow.
+fn offset_position(base: usize, offset: i64) -> Option<usize> {
+	if offset >= 0 {
+		base.checked_add(usize::try_from(offset).ok()?)
+	} else {
+		base.checked_sub(usize::try_from(offset.unsigned_abs()).ok()?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Arena;
+
+	#[test]
+	fn read_chunks() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut reader = alloc.insert_bytes(b"0123456789").reader();
+
+		let mut chunk = [0; 4];
+		assert_eq!(reader.read(&mut chunk).unwrap(), 4);
+		assert_eq!(&chunk, b"0123");
+		assert_eq!(reader.read(&mut chunk).unwrap(), 4);
+		assert_eq!(&chunk, b"4567");
+		assert_eq!(reader.read(&mut chunk).unwrap(), 2);
+		assert_eq!(&chunk[..2], b"89");
+		assert_eq!(reader.read(&mut chunk).unwrap(), 0);
+		assert_eq!(reader.position(), 10);
+	}
+
+	#[test]
+	fn read_exact_past_end() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut reader = alloc.insert_bytes(b"abc").reader();
+
+		let mut buf = [0; 4];
+		assert!(reader.read_exact(&mut buf).is_err());
+		assert_eq!(reader.position(), 0);
+		assert_eq!(reader.remaining_slice(), b"abc");
+		assert_eq!(&*reader.into_inner(), b"abc");
+	}
+
+	#[test]
+	fn read_structure() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut data = std::vec::Vec::new();
+		data.extend_from_slice(&0xDEADBEEFu32.to_le_bytes());
+		data.extend_from_slice(&7u16.to_le_bytes());
+		data.extend_from_slice(b"name\nrest");
+		let mut reader = alloc.insert_bytes(&data).reader();
+
+		let mut word = [0; 4];
+		reader.read_exact(&mut word).unwrap();
+		assert_eq!(u32::from_le_bytes(word), 0xDEADBEEF);
+		let mut half = [0; 2];
+		reader.read_exact(&mut half).unwrap();
+		assert_eq!(u16::from_le_bytes(half), 7);
+
+		let mut line = std::string::String::new();
+		reader.read_line(&mut line).unwrap();
+		assert_eq!(line, "name\n");
+		assert_eq!(reader.fill_buf().unwrap(), b"rest");
+		assert_eq!(reader.position(), 11);
+	}
+
+	#[test]
+	fn seek() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut reader = alloc.insert_bytes(b"0123456789").reader();
+
+		let mut buf = [0; 2];
+		reader.read_exact(&mut buf).unwrap();
+		assert_eq!(reader.seek(SeekFrom::Current(-1)).unwrap(), 1);
+		reader.read_exact(&mut buf).unwrap();
+		assert_eq!(&buf, b"12");
+
+		assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 8);
+		reader.read_exact(&mut buf).unwrap();
+		assert_eq!(&buf, b"89");
+
+		assert_eq!(reader.seek(SeekFrom::Start(20)).unwrap(), 20);
+		assert_eq!(reader.read(&mut buf).unwrap(), 0);
+		assert!(reader.seek(SeekFrom::Current(-30)).is_err());
+		assert_eq!(reader.stream_position().unwrap(), 20);
+	}
+}
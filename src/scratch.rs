@@ -0,0 +1,69 @@
+use core::cell::RefCell;
+
+use crate::{Arena, ArenaAlloc};
+
+std::thread_local! {
+	// The arena is taken out while it's in use, so nested calls can't get it.
+	static SCRATCH: RefCell<Option<Arena>> = const { RefCell::new(None) };
+}
+
+/// Runs ``f`` with an allocator from a scratch [Arena] that belongs to the current thread, so
+/// that hot functions can allocate without having an [Arena] passed to them.
+///
+/// The arena is created the first time it's needed, and reused by later calls, unless they ask
+/// for a larger ``size`` than it has. Like with [Arena::scope], nothing allocated can escape ``f``.
+///
+/// Nested calls on the same thread can't use the same arena, so they get a new one instead.
+///
+/// # Panics
+/// * If a new arena is needed and ``size`` is 0.
+/// * If a new arena is needed but can't be allocated.
+///
+/// ```
+/// let sum = arena::with_scratch(1024, |alloc| {
+///     let numbers = alloc.insert_all(0..10u32);
+///     numbers.iter().sum::<u32>()
+/// });
+/// assert_eq!(sum, 45);
+/// ```
+pub fn with_scratch<R>(size: usize, f: impl for<'a> FnOnce(&mut ArenaAlloc<'a>) -> R) -> R {
+	let mut arena = match SCRATCH.with(|scratch| scratch.borrow_mut().take()) {
+		Some(arena) if arena.length >= size => arena,
+		_ => Arena::new(size),
+	};
+
+	let result = arena.scope(f);
+	SCRATCH.with(|scratch| *scratch.borrow_mut() = Some(arena));
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reuse() {
+		let first = with_scratch(64, |alloc| alloc.insert(1u8).as_ptr() as usize);
+		let second = with_scratch(64, |alloc| alloc.insert(2u8).as_ptr() as usize);
+		assert_eq!(first, second);
+	}
+
+	#[test]
+	fn nested_dont_alias() {
+		with_scratch(64, |outer| {
+			let value = outer.insert(1u64);
+			with_scratch(64, |inner| {
+				let other = inner.insert(2u64);
+				assert!(!outer.contains(&other));
+				assert!(!inner.contains(&value));
+			});
+			assert_eq!(*value, 1);
+		});
+	}
+
+	#[test]
+	fn larger_size() {
+		with_scratch(16, |_| ());
+		with_scratch(1024, |alloc| assert_eq!(alloc.capacity(), 1024));
+	}
+}
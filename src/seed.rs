@@ -0,0 +1,196 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+
+use serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{ArenaAlloc, ArenaBox};
+
+impl<T> Serialize for ArenaBox<'_, T> where T: ?Sized + Serialize {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where S: Serializer
+	{
+		T::serialize(self, serializer)
+	}
+}
+
+/// Deserializes a value straight into an allocator, as an [ArenaBox]. A box can't implement
+/// [Deserialize] on its own, since it needs to know which allocator to use.
+///
+/// This works for ``ArenaSeed<T>``, and for ``ArenaSeed<[T]>``, which inserts the elements of a
+/// sequence without collecting them anywhere else first.
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use arena::ArenaSeed;
+///
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let mut json = serde_json::Deserializer::from_str("[1, 2, 3]");
+/// let numbers = ArenaSeed::<[u32]>::new(&mut alloc).deserialize(&mut json).unwrap();
+/// assert_eq!(&*numbers, &[1, 2, 3]);
+/// ```
+pub struct ArenaSeed<'s, 'a, T: ?Sized> {
+	alloc: &'s mut ArenaAlloc<'a>,
+	_phantom: PhantomData<fn() -> *const T>,
+}
+
+impl<'s, 'a, T: ?Sized> ArenaSeed<'s, 'a, T> {
+	/// Creates a seed that inserts the value into ``alloc``.
+	pub fn new(alloc: &'s mut ArenaAlloc<'a>) -> Self {
+		Self { alloc, _phantom: PhantomData }
+	}
+}
+
+impl<'de, 'a, T> DeserializeSeed<'de> for ArenaSeed<'_, 'a, T> where T: Deserialize<'de> + 'a {
+	type Value = ArenaBox<'a, T>;
+
+	fn deserialize<D>(self, deserializer: D) -> Result<ArenaBox<'a, T>, D::Error>
+		where D: Deserializer<'de>
+	{
+		let value = T::deserialize(deserializer)?;
+		self.alloc.try_insert(value).ok_or_else(|| de::Error::custom("Arena ran out of space"))
+	}
+}
+
+impl<'de, 'a, T> DeserializeSeed<'de> for ArenaSeed<'_, 'a, [T]> where T: Deserialize<'de> + 'a {
+	type Value = ArenaBox<'a, [T]>;
+
+	fn deserialize<D>(self, deserializer: D) -> Result<ArenaBox<'a, [T]>, D::Error>
+		where D: Deserializer<'de>
+	{
+		deserializer.deserialize_seq(SliceVisitor { alloc: self.alloc, _phantom: PhantomData })
+	}
+}
+
+struct SliceVisitor<'s, 'a, T> {
+	alloc: &'s mut ArenaAlloc<'a>,
+	_phantom: PhantomData<fn() -> T>,
+}
+
+impl<'de, 'a, T> Visitor<'de> for SliceVisitor<'_, 'a, T> where T: Deserialize<'de> + 'a {
+	type Value = ArenaBox<'a, [T]>;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("a sequence")
+	}
+
+	fn visit_seq<A>(self, mut seq: A) -> Result<ArenaBox<'a, [T]>, A::Error>
+		where A: SeqAccess<'de>
+	{
+		// The size hint comes from the input, so it's not trusted with more than what fits.
+		let fits = self.alloc.remaining() / mem::size_of::<T>().max(1);
+		let hint = seq.size_hint().unwrap_or(0).min(fits);
+		let mut error = None;
+		let slice = self.alloc.try_insert_all(SeqIter {
+			seq: &mut seq,
+			hint,
+			error: &mut error,
+			_phantom: PhantomData,
+		});
+
+		// If an element failed, the ones before it are dropped along with the slice.
+		match (error, slice) {
+			(Some(error), _) => Err(error),
+			(None, Some(slice)) => Ok(slice),
+			(None, None) => Err(de::Error::custom("Arena ran out of space")),
+		}
+	}
+}
+
+/// Iterates over the elements of a sequence, and stops at the first error, which is kept.
+struct SeqIter<'r, A, E, T> {
+	seq: &'r mut A,
+	hint: usize,
+	error: &'r mut Option<E>,
+	_phantom: PhantomData<fn() -> T>,
+}
+
+impl<'de, A, T> Iterator for SeqIter<'_, A, A::Error, T>
+	where A: SeqAccess<'de>, T: Deserialize<'de>
+{
+	type Item = T;
+
+	fn next(&mut self) -> Option<T> {
+		match self.seq.next_element() {
+			Ok(element) => {
+				self.hint = self.hint.saturating_sub(1);
+				element
+			}
+			Err(error) => {
+				*self.error = Some(error);
+				None
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.hint, None)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Arena;
+	use serde::Deserialize;
+	use std::string::{String, ToString};
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct Point {
+		x: i32,
+		y: i32,
+	}
+
+	fn from_json<'de, S>(seed: S, json: &'de str) -> serde_json::Result<S::Value>
+		where S: DeserializeSeed<'de>
+	{
+		seed.deserialize(&mut serde_json::Deserializer::from_str(json))
+	}
+
+	#[test]
+	fn round_trip() {
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+
+		let point = alloc.insert(Point { x: 1, y: -2 });
+		let json = serde_json::to_string(&point).unwrap();
+		assert_eq!(json, r#"{"x":1,"y":-2}"#);
+		let copy = from_json(ArenaSeed::<Point>::new(&mut alloc), &json).unwrap();
+		assert_eq!(*copy, Point { x: 1, y: -2 });
+
+		let points = alloc.insert_all((0..3).map(|i| Point { x: i, y: i * 10 }));
+		let json = serde_json::to_string(&points).unwrap();
+		let copy = from_json(ArenaSeed::<[Point]>::new(&mut alloc), &json).unwrap();
+		assert_eq!(*copy, *points);
+	}
+
+	#[test]
+	fn strings() {
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+		let words = from_json(ArenaSeed::<[String]>::new(&mut alloc), r#"["a", "bc"]"#).unwrap();
+		assert_eq!(*words, ["a", "bc"]);
+		assert_eq!(serde_json::to_string(&alloc.insert_str("hi")).unwrap(), r#""hi""#);
+	}
+
+	#[test]
+	fn errors() {
+		let mut arena = Arena::with_align(16, 4);
+
+		{
+			let mut alloc = arena.begin_alloc();
+			let numbers = from_json(ArenaSeed::<[u32]>::new(&mut alloc), "[1, 2]").unwrap();
+			assert_eq!(*numbers, [1, 2]);
+		}
+
+		let mut alloc = arena.begin_alloc();
+		let error = from_json(ArenaSeed::<[u32]>::new(&mut alloc), "[1, 2, 3, 4, 5]").unwrap_err();
+		assert!(error.to_string().contains("Arena ran out of space"));
+
+		let mut alloc = arena.begin_alloc();
+		assert!(from_json(ArenaSeed::<[u32]>::new(&mut alloc), r#"[1, "2"]"#).is_err());
+		assert!(from_json(ArenaSeed::<[u64; 4]>::new(&mut alloc), "[1, 2, 3, 4]").is_err());
+	}
+}
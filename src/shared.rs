@@ -0,0 +1,198 @@
+use core::alloc::{AllocError, Allocator, Layout};
+use core::cell::{Cell, UnsafeCell};
+use core::mem::ManuallyDrop;
+use core::ptr::NonNull;
+
+use crate::{Arena, ArenaAlloc};
+
+/// An [Arena] that can begin a batch of allocations through a shared reference, so that it can
+/// be stored in a wrapper that hands out allocators.
+///
+/// Only one [SharedArenaAlloc] can be active at a time. Beginning another one while it's active
+/// returns None instead of a second allocator.
+///
+/// ```
+/// #![feature(allocator_api)]
+///
+/// let shared = arena::SharedArena::new(arena::Arena::new(64));
+/// let alloc = shared.try_begin().unwrap();
+/// assert!(shared.is_active());
+/// assert!(shared.try_begin().is_none());
+/// drop(alloc);
+/// assert!(!shared.is_active());
+/// ```
+pub struct SharedArena {
+	// INVARIANTS:
+	// * While active is true, the arena is only used through the allocator that set it.
+	// * No reference to arena lives past a single method call otherwise.
+	arena: UnsafeCell<Arena>,
+	active: Cell<bool>,
+}
+
+impl SharedArena {
+	/// Wraps an arena, which starts out inactive.
+	pub fn new(arena: Arena) -> Self {
+		Self {
+			arena: UnsafeCell::new(arena),
+			active: Cell::new(false),
+		}
+	}
+
+	/// Returns whether an allocator from [SharedArena::try_begin] is currently using the arena.
+	pub fn is_active(&self) -> bool {
+		self.active.get()
+	}
+
+	/// Tries to begin a batch of allocations. If the arena is already active, it will return
+	/// None. The arena becomes inactive again once the allocator is dropped.
+	pub fn try_begin(&self) -> Option<SharedArenaAlloc<'_>> {
+		if self.active.replace(true) {
+			return None;
+		}
+
+		// SAFETY: The arena wasn't active, so nothing else is using it, and it stays active until
+		// the allocator is gone.
+		let alloc = unsafe { (*self.arena.get()).begin_alloc() };
+		Some(SharedArenaAlloc {
+			alloc: ManuallyDrop::new(UnsafeCell::new(alloc)),
+			active: Some(&self.active),
+		})
+	}
+
+	/// Borrows the arena mutably. Since nothing else can be borrowing it, this also makes the
+	/// arena inactive, even if an allocator was leaked or unwrapped.
+	pub fn get_mut(&mut self) -> &mut Arena {
+		self.active.set(false);
+		self.arena.get_mut()
+	}
+
+	/// Unwraps the arena.
+	pub fn into_inner(self) -> Arena {
+		self.arena.into_inner()
+	}
+}
+
+/// An [ArenaAlloc] that can allocate through a shared reference, so that it can be used as an
+/// [Allocator] for collections like [Vec](alloc::vec::Vec) and [Box](alloc::boxed::Box).
+///
+/// Deallocating doesn't do anything, the memory is reused once the [Arena](crate::Arena) starts
+/// a new batch of allocations, like with [ArenaAlloc].
+pub struct SharedArenaAlloc<'a> {
+	// INVARIANTS:
+	// * No reference to alloc lives past a single method call. It is never shared between
+	//   threads, since UnsafeCell is not Sync.
+	// * alloc is only taken out in into_inner, which doesn't drop the allocator.
+	// * If active is Some, the allocator was begun by a SharedArena, which stays active until
+	//   the allocator is dropped.
+	alloc: ManuallyDrop<UnsafeCell<ArenaAlloc<'a>>>,
+	active: Option<&'a Cell<bool>>,
+}
+
+impl<'a> SharedArenaAlloc<'a> {
+	/// Wraps an allocator, continuing to allocate where it left off.
+	pub fn new(alloc: ArenaAlloc<'a>) -> Self {
+		Self {
+			alloc: ManuallyDrop::new(UnsafeCell::new(alloc)),
+			active: None,
+		}
+	}
+
+	/// Returns the number of bytes left in the [Arena](crate::Arena) for this batch of
+	/// allocations. See [ArenaAlloc::remaining].
+	pub fn remaining(&self) -> usize {
+		// SAFETY: See the invariants.
+		unsafe { (*self.alloc.get()).remaining() }
+	}
+
+	/// Unwraps the allocator, so it can be used for inserting [ArenaBox](crate::ArenaBox)es.
+	///
+	/// If the allocator came from a [SharedArena], the arena stays active, since the allocator
+	/// still borrows it.
+	pub fn into_inner(self) -> ArenaAlloc<'a> {
+		let mut this = ManuallyDrop::new(self);
+		// SAFETY: The allocator isn't dropped, so alloc is only taken out once.
+		unsafe { ManuallyDrop::take(&mut this.alloc).into_inner() }
+	}
+}
+
+impl Drop for SharedArenaAlloc<'_> {
+	fn drop(&mut self) {
+		// SAFETY: The allocator is being dropped, so alloc isn't used again.
+		unsafe { ManuallyDrop::drop(&mut self.alloc) };
+		if let Some(active) = self.active {
+			active.set(false);
+		}
+	}
+}
+
+unsafe impl Allocator for SharedArenaAlloc<'_> {
+	fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+		// SAFETY: See the invariants. try_alloc_layout doesn't call any code that could use the
+		// allocator again.
+		let ptr = unsafe { (*self.alloc.get()).try_alloc_layout(layout) }.ok_or(AllocError)?;
+		let ptr = NonNull::new(ptr).ok_or(AllocError)?;
+		Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+	}
+
+	unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+		// The memory is reclaimed once the arena starts a new batch.
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::SharedArena;
+	use crate::Arena;
+	use std::boxed::Box;
+	use std::vec::Vec;
+
+	#[test]
+	fn vec_in_arena() {
+		let mut arena = Arena::new(1024);
+		let shared = arena.begin_shared();
+		let mut vec = Vec::new_in(&shared);
+
+		for i in 0u32.. {
+			if vec.try_reserve(1).is_err() {
+				break;
+			}
+			vec.push(i);
+		}
+
+		assert!(!vec.is_empty());
+		assert!(vec.iter().enumerate().all(|(i, &v)| v == i as u32));
+		assert!(shared.remaining() < vec.capacity() * std::mem::size_of::<u32>());
+	}
+
+	#[test]
+	fn box_in_arena() {
+		let mut arena = Arena::new(64);
+		let shared = arena.begin_shared();
+		let boxed = Box::new_in(5u64, &shared);
+		assert_eq!(*boxed, 5);
+		assert!(Box::try_new_in([0u8; 128], &shared).is_err());
+	}
+
+	#[test]
+	fn begin_while_active() {
+		let mut shared = SharedArena::new(Arena::new(64));
+		assert!(!shared.is_active());
+
+		let first = shared.try_begin().unwrap();
+		assert!(shared.is_active());
+		assert!(shared.try_begin().is_none());
+		let boxed = Box::new_in(1u32, &first);
+		drop(boxed);
+		drop(first);
+		assert!(!shared.is_active());
+
+		{
+			let _alloc = shared.try_begin().unwrap().into_inner();
+			assert!(shared.is_active());
+		}
+		assert!(shared.try_begin().is_none());
+		shared.get_mut();
+		assert!(!shared.is_active());
+		assert!(shared.try_begin().is_some());
+	}
+}
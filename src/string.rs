@@ -0,0 +1,192 @@
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+
+use crate::ArenaBox;
+
+/// A string with a fixed capacity, backed by memory from an [Arena](crate::Arena). Create one
+/// with [ArenaAlloc::string_with_capacity](crate::ArenaAlloc::string_with_capacity).
+///
+/// Like [ArenaVec](crate::ArenaVec), it never reallocates. Appending something that doesn't fit
+/// hands it back without writing any of it.
+///
+/// ```
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let mut string = alloc.string_with_capacity(8);
+/// assert!(string.push_str("hello").is_ok());
+/// assert_eq!(string.push_str(" world"), Err(" world"));
+/// assert_eq!(&*string.into_boxed_str(), "hello");
+/// ```
+pub struct ArenaString<'a> {
+	// INVARIANTS:
+	// * The first len bytes of buffer are initialized, and are valid utf-8.
+	// * len is never larger than the length of buffer.
+	buffer: ArenaBox<'a, [MaybeUninit<u8>]>,
+	len: usize,
+}
+
+impl<'a> ArenaString<'a> {
+	/// Creates an empty string that can use all of ``buffer``.
+	pub(crate) fn new(buffer: ArenaBox<'a, [MaybeUninit<u8>]>) -> Self {
+		Self { buffer, len: 0 }
+	}
+
+	/// Returns the length of the string in bytes.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns true if the string is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the number of bytes the string can hold.
+	pub fn capacity(&self) -> usize {
+		self.buffer.len()
+	}
+
+	/// Appends a string slice. If it doesn't fit, nothing is written and the slice is given back
+	/// as an error.
+	pub fn push_str<'s>(&mut self, string: &'s str) -> Result<(), &'s str> {
+		let bytes = string.as_bytes();
+		match self.buffer.get_mut(self.len..self.len + bytes.len()) {
+			Some(slots) => {
+				// SAFETY: MaybeUninit<u8> has the same layout as u8, and the slices can't overlap
+				// since the buffer is owned by this string.
+				unsafe {
+					let dst = slots.as_mut_ptr() as *mut u8;
+					core::ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+				}
+				self.len += bytes.len();
+				Ok(())
+			}
+			None => Err(string),
+		}
+	}
+
+	/// Appends a character. If it doesn't fit, nothing is written and the character is given back
+	/// as an error.
+	pub fn push(&mut self, c: char) -> Result<(), char> {
+		let mut encoded = [0; 4];
+		self.push_str(c.encode_utf8(&mut encoded)).map_err(|_| c)
+	}
+
+	/// Returns the string that has been written so far.
+	pub fn as_str(&self) -> &str {
+		// SAFETY: The first len bytes are initialized and valid utf-8.
+		unsafe {
+			let bytes = core::slice::from_raw_parts(self.buffer.as_ptr() as *const u8, self.len);
+			core::str::from_utf8_unchecked(bytes)
+		}
+	}
+
+	/// Converts the string into a boxed str of the bytes that were written. The unused capacity
+	/// is not given back to the arena.
+	pub fn into_boxed_str(self) -> ArenaBox<'a, str> {
+		let len = self.len;
+		let bytes = self.buffer.leak();
+		// SAFETY: The first len bytes are initialized and valid utf-8, and the buffer was leaked
+		// from a box, so nothing else owns it.
+		unsafe {
+			let bytes = core::slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut u8, len);
+			ArenaBox::from_raw(core::str::from_utf8_unchecked_mut(bytes))
+		}
+	}
+}
+
+impl Deref for ArenaString<'_> {
+	type Target = str;
+
+	fn deref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl fmt::Debug for ArenaString<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+impl fmt::Display for ArenaString<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_str().fmt(f)
+	}
+}
+
+/// Appends to the string. If a piece doesn't fit, it's not written, and [fmt::Error] is
+/// returned.
+impl fmt::Write for ArenaString<'_> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		self.push_str(s).map_err(|_| fmt::Error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Arena;
+
+	#[test]
+	fn push() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut string = alloc.string_with_capacity(16);
+		assert!(string.is_empty());
+
+		string.push_str("abc").unwrap();
+		string.push('é').unwrap();
+		string.push('!').unwrap();
+		assert_eq!(string.as_str(), "abcé!");
+		assert_eq!(string.len(), 6);
+		assert_eq!(string.capacity(), 16);
+	}
+
+	#[test]
+	fn straddle_capacity() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut string = alloc.string_with_capacity(4);
+
+		string.push_str("abc").unwrap();
+		// Takes 2 bytes, but only 1 is left.
+		assert_eq!(string.push('é'), Err('é'));
+		assert_eq!(string.push_str("dé"), Err("dé"));
+		assert_eq!(string.as_str(), "abc");
+		string.push('d').unwrap();
+
+		let boxed = string.into_boxed_str();
+		assert_eq!(&*boxed, "abcd");
+		assert!(core::str::from_utf8(boxed.as_bytes()).is_ok());
+	}
+
+	#[test]
+	fn into_boxed_str_written_only() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut string = alloc.string_with_capacity(32);
+		string.push_str("hi").unwrap();
+
+		let boxed = string.into_boxed_str();
+		assert_eq!(boxed.len(), 2);
+		assert_eq!(&*boxed, "hi");
+	}
+
+	#[test]
+	fn fmt_write() {
+		use core::fmt::Write;
+
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut string = alloc.string_with_capacity(12);
+		let name = "id";
+		write!(string, "{}-{:03}", name, 7).unwrap();
+		assert_eq!(string.as_str(), "id-007");
+
+		assert!(write!(string, "{}", 12345678).is_err());
+		assert_eq!(string.as_str(), "id-007");
+		string.write_char('!').unwrap();
+		assert_eq!(string.as_str(), "id-007!");
+	}
+}
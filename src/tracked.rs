@@ -0,0 +1,160 @@
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::mem;
+
+use crate::ArenaAlloc;
+
+/// An allocator that remembers which of its values need to be dropped, and drops all of them
+/// when the allocator itself is dropped. Create one with
+/// [Arena::begin_alloc_tracked](crate::Arena::begin_alloc_tracked).
+///
+/// Instead of [ArenaBox](crate::ArenaBox)es, inserting gives you references that live as long as
+/// the allocator, so the order the values go away in doesn't have to match the order they were
+/// created in. The values are dropped in the reverse order they were inserted.
+///
+/// The values have to outlive the [Arena](crate::Arena) borrow, so they can't borrow from each
+/// other. Otherwise a destructor could see a value that was already dropped.
+///
+/// ```
+/// let mut arena = arena::Arena::new(256);
+/// let alloc = arena.begin_alloc_tracked();
+/// let a = alloc.insert(String::from("a"));
+/// let b = alloc.insert(String::from("b"));
+/// a.push_str(b);
+/// assert_eq!(a.as_str(), "ab");
+/// // Both strings are dropped here.
+/// ```
+pub struct TrackedAlloc<'a> {
+	// INVARIANTS:
+	// * No reference to alloc or drops lives past a single method call, and those methods don't
+	//   call any code that could use the allocator again. It is never shared between threads,
+	//   since UnsafeCell is not Sync.
+	// * Every entry in drops points to an initialized value of the type the function drops,
+	//   allocated from alloc, which hasn't been dropped yet.
+	alloc: UnsafeCell<ArenaAlloc<'a>>,
+	drops: UnsafeCell<Vec<TrackedValue>>,
+}
+
+/// A value that has to be dropped, and the function that drops it.
+type TrackedValue = (*mut u8, unsafe fn(*mut u8));
+
+impl<'a> TrackedAlloc<'a> {
+	/// Wraps an allocator, continuing to allocate where it left off.
+	pub(crate) fn new(alloc: ArenaAlloc<'a>) -> Self {
+		Self {
+			alloc: UnsafeCell::new(alloc),
+			drops: UnsafeCell::new(Vec::new()),
+		}
+	}
+
+	/// Tries to allocate a space for T and insert the value into it. If there isn't enough space
+	/// for T, it will return None.
+	#[allow(clippy::mut_from_ref)]
+	pub fn try_insert<T: 'a>(&self, value: T) -> Option<&mut T> {
+		// SAFETY: See the invariants.
+		let ptr = unsafe { (*self.alloc.get()).try_alloc::<T>()? };
+
+		// SAFETY: The pointer was just allocated, and is only handed out once. The value is
+		// written before it's registered, so it's never dropped uninitialized.
+		unsafe {
+			ptr.write(value);
+			if mem::needs_drop::<T>() {
+				(*self.drops.get()).push((ptr as *mut u8, drop_value::<T>));
+			}
+			Some(&mut *ptr)
+		}
+	}
+
+	/// Allocates a space for T and inserts the value into it.
+	///
+	/// # Panics
+	/// * If there isn't enough space in the [Arena](crate::Arena).
+	#[allow(clippy::mut_from_ref)]
+	pub fn insert<T: 'a>(&self, value: T) -> &mut T {
+		self.try_insert(value).expect("Arena ran out of space")
+	}
+
+	/// Returns the number of bytes left in the [Arena](crate::Arena) for this batch of
+	/// allocations. See [ArenaAlloc::remaining].
+	pub fn remaining(&self) -> usize {
+		// SAFETY: See the invariants.
+		unsafe { (*self.alloc.get()).remaining() }
+	}
+}
+
+impl Drop for TrackedAlloc<'_> {
+	fn drop(&mut self) {
+		for (ptr, drop) in self.drops.get_mut().drain(..).rev() {
+			// SAFETY: See the invariants. The references to the values borrowed the allocator,
+			// so they are gone.
+			unsafe { drop(ptr) }
+		}
+	}
+}
+
+/// Drops the T the pointer points to.
+///
+/// # Safety
+/// * ``ptr`` has to point to an initialized T, that isn't used after this.
+unsafe fn drop_value<T>(ptr: *mut u8) {
+	core::ptr::drop_in_place(ptr as *mut T);
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Arena;
+	use std::rc::Rc;
+	use std::string::{String, ToString};
+	use std::vec::Vec;
+
+	#[test]
+	fn drops_once() {
+		let counter = Rc::new(());
+		let mut arena = Arena::new(1024);
+		{
+			let alloc = arena.begin_alloc_tracked();
+			let strings: Vec<&mut (String, Rc<()>)> = (0..10)
+				.map(|i| alloc.insert((String::from("value ") + &i.to_string(), counter.clone())))
+				.collect();
+			assert_eq!(strings[3].0, "value 3");
+			assert_eq!(Rc::strong_count(&counter), 11);
+		}
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn drop_order() {
+		use core::cell::RefCell;
+
+		struct Push<'b>(&'b RefCell<Vec<u32>>, u32);
+
+		impl Drop for Push<'_> {
+			fn drop(&mut self) {
+				self.0.borrow_mut().push(self.1);
+			}
+		}
+
+		let order = RefCell::new(Vec::new());
+		let mut arena = Arena::new(256);
+		{
+			let alloc = arena.begin_alloc_tracked();
+			for i in 0..3 {
+				alloc.insert(Push(&order, i));
+			}
+			// Values that don't need dropping aren't tracked.
+			alloc.insert(5u64);
+		}
+		assert_eq!(*order.borrow(), [2, 1, 0]);
+	}
+
+	#[test]
+	fn out_of_space() {
+		let counter = Rc::new(());
+		let mut arena = Arena::new(16);
+		let alloc = arena.begin_alloc_tracked();
+		assert!(alloc.try_insert([0u8; 32]).is_none());
+		assert!(alloc.try_insert(counter.clone()).is_some());
+		drop(alloc);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+}
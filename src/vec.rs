@@ -0,0 +1,285 @@
+use core::fmt;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::ArenaBox;
+
+/// A vector with a fixed capacity, backed by memory from an [Arena](crate::Arena). Create one
+/// with [ArenaAlloc::vec_with_capacity](crate::ArenaAlloc::vec_with_capacity).
+///
+/// The arena can't move things around, so the vector never reallocates. Pushing to a full vector
+/// hands the value back instead.
+///
+/// ```
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let mut vec = alloc.vec_with_capacity(2);
+/// assert!(vec.push(1).is_ok());
+/// assert!(vec.push(2).is_ok());
+/// assert_eq!(vec.push(3), Err(3));
+/// assert_eq!(vec.as_slice(), &[1, 2]);
+/// ```
+pub struct ArenaVec<'a, T> {
+	// INVARIANTS:
+	// * The first len elements of buffer are initialized.
+	// * len is never larger than the length of buffer.
+	buffer: ArenaBox<'a, [MaybeUninit<T>]>,
+	len: usize,
+}
+
+impl<'a, T> ArenaVec<'a, T> {
+	/// Creates an empty vector that can use all of ``buffer``.
+	pub(crate) fn new(buffer: ArenaBox<'a, [MaybeUninit<T>]>) -> Self {
+		Self { buffer, len: 0 }
+	}
+
+	/// Returns the number of elements in the vector.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Returns true if the vector contains no elements.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Returns the number of elements the vector can hold.
+	pub fn capacity(&self) -> usize {
+		self.buffer.len()
+	}
+
+	/// Appends an element to the back of the vector. If the vector is full, the value is given
+	/// back as an error.
+	pub fn push(&mut self, value: T) -> Result<(), T> {
+		match self.buffer.get_mut(self.len) {
+			Some(slot) => {
+				slot.write(value);
+				self.len += 1;
+				Ok(())
+			}
+			None => Err(value),
+		}
+	}
+
+	/// Pushes the items of an iterator until it runs out or the vector is full. If an item
+	/// doesn't fit, it's given back as an error, along with the items that weren't taken from the
+	/// iterator yet.
+	pub fn try_extend<I>(&mut self, items: I) -> Result<(), (T, I::IntoIter)>
+		where I: IntoIterator<Item = T>
+	{
+		let mut items = items.into_iter();
+		for item in &mut items {
+			if let Err(item) = self.push(item) {
+				return Err((item, items));
+			}
+		}
+		Ok(())
+	}
+
+	/// Removes the last element and returns it, or None if the vector is empty.
+	pub fn pop(&mut self) -> Option<T> {
+		if self.len == 0 {
+			return None;
+		}
+
+		self.len -= 1;
+		// SAFETY: The element was initialized, and since len was decreased, it won't be read or
+		// dropped again.
+		unsafe { Some(self.buffer[self.len].as_ptr().read()) }
+	}
+
+	/// Returns the initialized elements as a slice.
+	pub fn as_slice(&self) -> &[T] {
+		// SAFETY: The first len elements are initialized.
+		unsafe { core::slice::from_raw_parts(self.buffer.as_ptr() as *const T, self.len) }
+	}
+
+	/// Returns the initialized elements as a mutable slice.
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		// SAFETY: The first len elements are initialized.
+		unsafe { core::slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut T, self.len) }
+	}
+
+	/// Converts the vector into a boxed slice of the initialized elements. The unused capacity is
+	/// not given back to the arena.
+	pub fn into_boxed_slice(self) -> ArenaBox<'a, [T]> {
+		let mut vec = mem::ManuallyDrop::new(self);
+		let len = vec.len;
+		// SAFETY: The vector isn't dropped, so the buffer is only owned by the new box, which
+		// only covers the initialized elements.
+		unsafe {
+			let ptr = vec.buffer.as_mut_ptr() as *mut T;
+			ArenaBox::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len))
+		}
+	}
+}
+
+impl<T> Drop for ArenaVec<'_, T> {
+	fn drop(&mut self) {
+		// SAFETY: The first len elements are initialized, and they are not used again.
+		unsafe { core::ptr::drop_in_place(self.as_mut_slice()) }
+	}
+}
+
+impl<T> Deref for ArenaVec<'_, T> {
+	type Target = [T];
+
+	fn deref(&self) -> &[T] {
+		self.as_slice()
+	}
+}
+
+impl<T> DerefMut for ArenaVec<'_, T> {
+	fn deref_mut(&mut self) -> &mut [T] {
+		self.as_mut_slice()
+	}
+}
+
+/// Pushes every item of the iterator.
+///
+/// # Panics
+/// * If the vector fills up before the iterator runs out. The items that fit are still pushed.
+impl<T> Extend<T> for ArenaVec<'_, T> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+		if self.try_extend(items).is_err() {
+			panic!("ArenaVec ran out of capacity");
+		}
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaVec<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.as_slice().fmt(f)
+	}
+}
+
+/// Appends as many bytes as fit in the remaining capacity, like writing to a ``&mut [u8]``.
+/// Once the vector is full, writes return 0, so [write_all](io::Write::write_all) fails with
+/// [WriteZero](io::ErrorKind::WriteZero).
+#[cfg(feature = "std")]
+impl io::Write for ArenaVec<'_, u8> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let written = buf.len().min(self.capacity() - self.len);
+		// SAFETY: MaybeUninit<u8> has the same layout as u8, there is space for written more
+		// bytes, and the slices can't overlap since the buffer is owned by this vector.
+		unsafe {
+			let end = (self.buffer.as_mut_ptr() as *mut u8).add(self.len);
+			core::ptr::copy_nonoverlapping(buf.as_ptr(), end, written);
+		}
+		self.len += written;
+		Ok(written)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Arena;
+	use std::rc::Rc;
+
+	#[test]
+	fn push_to_capacity() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut vec = alloc.vec_with_capacity(3);
+		assert!(vec.is_empty());
+		assert_eq!(vec.capacity(), 3);
+
+		for i in 0..3u32 {
+			assert_eq!(vec.push(i), Ok(()));
+		}
+		assert_eq!(vec.push(3), Err(3));
+		assert_eq!(vec.len(), 3);
+		vec.as_mut_slice()[0] = 10;
+		assert_eq!(vec.as_slice(), &[10, 1, 2]);
+	}
+
+	#[test]
+	fn pop() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut vec = alloc.vec_with_capacity(2);
+		vec.push(1u8).unwrap();
+		vec.push(2u8).unwrap();
+
+		assert_eq!(vec.pop(), Some(2));
+		assert_eq!(vec.push(3), Ok(()));
+		assert_eq!(vec.pop(), Some(3));
+		assert_eq!(vec.pop(), Some(1));
+		assert_eq!(vec.pop(), None);
+	}
+
+	#[test]
+	fn drop_initialized_only() {
+		let counter = Rc::new(());
+		let mut arena = Arena::new(256);
+		let mut alloc = arena.begin_alloc();
+
+		let mut vec = alloc.vec_with_capacity(8);
+		for _ in 0..3 {
+			vec.push(counter.clone()).unwrap();
+		}
+		assert_eq!(Rc::strong_count(&counter), 4);
+		drop(vec.pop());
+		assert_eq!(Rc::strong_count(&counter), 3);
+		drop(vec);
+		assert_eq!(Rc::strong_count(&counter), 1);
+
+		let mut vec = alloc.vec_with_capacity(8);
+		vec.push(counter.clone()).unwrap();
+		vec.push(counter.clone()).unwrap();
+		let slice = vec.into_boxed_slice();
+		assert_eq!(slice.len(), 2);
+		assert_eq!(Rc::strong_count(&counter), 3);
+		drop(slice);
+		assert_eq!(Rc::strong_count(&counter), 1);
+	}
+
+	#[test]
+	fn extend() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut vec = alloc.vec_with_capacity(6);
+		vec.extend(0..3u8);
+		assert!(vec.try_extend([3, 4]).is_ok());
+		assert_eq!(vec.as_slice(), &[0, 1, 2, 3, 4]);
+
+		let (item, rest) = vec.try_extend(5..10).unwrap_err();
+		assert_eq!(item, 6);
+		assert!(rest.eq(7..10));
+		assert_eq!(vec.as_slice(), &[0, 1, 2, 3, 4, 5]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn extend_overflow() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut vec = alloc.vec_with_capacity(2);
+		vec.extend(0..3u8);
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn io_write() {
+		use std::io::{ErrorKind, Write};
+
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut vec = alloc.vec_with_capacity(24);
+		let (id, ok) = (7, true);
+		write!(vec, "{{\"id\":{},\"ok\":{}}}", id, ok).unwrap();
+		assert_eq!(vec.as_slice(), br#"{"id":7,"ok":true}"#);
+
+		let error = vec.write_all(b"[1,2,3,4]").unwrap_err();
+		assert_eq!(error.kind(), ErrorKind::WriteZero);
+		assert_eq!(vec.len(), 24);
+		assert_eq!(&vec[18..], b"[1,2,3");
+		assert_eq!(vec.write(b"4").unwrap(), 0);
+	}
+}
@@ -0,0 +1,116 @@
+use core::fmt;
+
+use crate::{ArenaAlloc, ArenaBox};
+
+/// Formats a string directly into an [Arena](crate::Arena), using [fmt::Write].
+///
+/// Each write bumps the allocator to make room for the new characters. This only works because
+/// the new characters end up directly after the old ones, so the writer borrows the allocator
+/// mutably, which makes sure nothing else is allocated until the writer is done.
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// let mut arena = arena::Arena::new(64);
+/// let mut alloc = arena.begin_alloc();
+/// let mut writer = alloc.string_writer();
+/// write!(writer, "{}+{}", 2, 3).unwrap();
+/// assert_eq!(&*writer.into_box(), "2+3");
+/// ```
+pub struct ArenaStringWriter<'a, 'b> {
+	// INVARIANTS:
+	// * The first len bytes after start are valid utf-8, and allocated from alloc.
+	// * alloc.head is start + len, unless len is 0.
+	alloc: &'b mut ArenaAlloc<'a>,
+	start: *mut u8,
+	len: usize,
+}
+
+impl<'a, 'b> ArenaStringWriter<'a, 'b> {
+	/// Creates a writer that appends to an empty string at the head of the allocator.
+	pub fn new(alloc: &'b mut ArenaAlloc<'a>) -> Self {
+		Self {
+			start: alloc.head,
+			alloc,
+			len: 0,
+		}
+	}
+
+	/// Returns the string that has been written so far.
+	pub fn as_str(&self) -> &str {
+		// SAFETY: See the invariants.
+		unsafe { core::str::from_utf8_unchecked(core::slice::from_raw_parts(self.start, self.len)) }
+	}
+
+	/// Finishes writing, and returns the string that was written.
+	pub fn into_box(self) -> ArenaBox<'a, str> {
+		let Self { alloc, start, len } = self;
+		if len == 0 {
+			return alloc.insert_str("");
+		}
+
+		// SAFETY: See the invariants. Nothing else can use the bytes, since they were allocated
+		// from the allocator.
+		unsafe {
+			let bytes = core::slice::from_raw_parts_mut(start, len);
+			ArenaBox::from_raw(core::str::from_utf8_unchecked_mut(bytes))
+		}
+	}
+}
+
+impl fmt::Write for ArenaStringWriter<'_, '_> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		if s.is_empty() {
+			return Ok(());
+		}
+
+		// The bytes have an alignment of 1, so they are allocated right at the head, which is
+		// directly after the bytes already written.
+		let ptr = self.alloc.try_alloc_array::<u8>(s.len()).ok_or(fmt::Error)?;
+		if self.len == 0 {
+			self.start = ptr;
+		}
+		debug_assert_eq!(ptr, self.start.wrapping_add(self.len));
+
+		// SAFETY: We just allocated space for the bytes.
+		unsafe { core::ptr::copy_nonoverlapping(s.as_ptr(), ptr, s.len()); }
+		self.len += s.len();
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Arena;
+	use core::fmt::Write;
+
+	#[test]
+	fn write() {
+		let mut arena = Arena::new(64);
+		let mut alloc = arena.begin_alloc();
+		let mut writer = alloc.string_writer();
+		write!(writer, "{}+{}", 2, 3).unwrap();
+		assert_eq!(writer.as_str(), "2+3");
+		let string = writer.into_box();
+		let other = alloc.insert_str("=5");
+		assert_eq!(&*string, "2+3");
+		assert_eq!(&*other, "=5");
+	}
+
+	#[test]
+	fn write_out_of_space() {
+		let mut arena = Arena::new(8);
+		let mut alloc = arena.begin_alloc();
+		let mut writer = alloc.string_writer();
+		writer.write_str("Hello").unwrap();
+		assert!(writer.write_str("World").is_err());
+		assert_eq!(&*writer.into_box(), "Hello");
+	}
+
+	#[test]
+	fn write_nothing() {
+		let mut arena = Arena::new(8);
+		let mut alloc = arena.begin_alloc();
+		assert_eq!(&*alloc.string_writer().into_box(), "");
+	}
+}